@@ -0,0 +1,30 @@
+#version 330
+
+// Input vertex attributes (from vertex shader)
+in vec2 fragTexCoord;
+in vec4 fragColor;
+
+// Input uniform values
+uniform sampler2D texture0;
+uniform vec4 colDiffuse;
+
+// Fog, set per screen / stage
+uniform vec4 fogColor;
+uniform float fogDensity;
+
+// Output fragment color
+out vec4 finalColor;
+
+void main()
+{
+    vec4 texelColor = texture(texture0, fragTexCoord)*colDiffuse*fragColor;
+
+    // Distance from the camera in view space
+    float dist = gl_FragCoord.z/gl_FragCoord.w;
+
+    // Exponential squared fog
+    float fogFactor = 1.0/exp((dist*fogDensity)*(dist*fogDensity));
+    fogFactor = clamp(fogFactor, 0.0, 1.0);
+
+    finalColor = vec4(mix(fogColor.rgb, texelColor.rgb, fogFactor), texelColor.a);
+}
@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::sync::OnceLock;
+
+pub const ARCHIVE_FILE_PATH: &str = "nmf.pak";
+pub const ARCHIVE_MAGIC: &[u8; 8] = b"NMFPAK\0\x01";
+
+static ARCHIVE: OnceLock<Option<Archive>> = OnceLock::new();
+
+/// Table of contents entry, offset is from the start of the archive
+struct Entry {
+    offset: u64,
+    size: u64,
+    checksum: u32,
+}
+
+/// Packed assets for release builds
+///
+/// magic, entry count (u32), entries, file data
+/// entry: path length (u16), path, offset (u64), size (u64), crc32 (u32)
+/// All numbers are little endian, paths use "/" and are relative to the game directory
+pub struct Archive {
+    file: File,
+    entries: HashMap<String, Entry>,
+}
+
+impl Archive {
+    /// Opens archive and reads its table of contents.
+    /// Entries that point past the end of the file are left out
+    pub fn open(path: &str) -> io::Result<Self> {
+        let mut file: File = File::open(path)?;
+        let file_size: u64 = file.metadata()?.len();
+        let mut magic: [u8; 8] = [0u8; 8];
+        file.read_exact(&mut magic)?;
+        if &magic != ARCHIVE_MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not an nmf archive",
+            ));
+        }
+
+        let count: u32 = u32::from_le_bytes(read_array(&mut file)?);
+        let mut entries: HashMap<String, Entry> = HashMap::new();
+        for _ in 0..count {
+            let path_length: u16 = u16::from_le_bytes(read_array(&mut file)?);
+            let mut path: Vec<u8> = vec![0u8; path_length as usize];
+            file.read_exact(&mut path)?;
+            let path: String = String::from_utf8(path)
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "path is not utf-8"))?;
+            let entry: Entry = Entry {
+                offset: u64::from_le_bytes(read_array(&mut file)?),
+                size: u64::from_le_bytes(read_array(&mut file)?),
+                checksum: u32::from_le_bytes(read_array(&mut file)?),
+            };
+            if entry
+                .offset
+                .checked_add(entry.size)
+                .is_none_or(|end| end > file_size)
+            {
+                log_error!(
+                    "{} in {} ends past the end of the archive, skipping it",
+                    path,
+                    ARCHIVE_FILE_PATH
+                );
+                continue;
+            }
+            entries.insert(path, entry);
+        }
+        Ok(Self { file, entries })
+    }
+
+    /// Reads entry and checks its checksum, None if it is absent or damaged
+    pub fn read(&self, path: &str) -> Option<Vec<u8>> {
+        let entry: &Entry = self.entries.get(path)?;
+        let mut data: Vec<u8> = vec![0u8; entry.size as usize];
+        let mut file: &File = &self.file;
+        file.seek(SeekFrom::Start(entry.offset)).ok()?;
+        file.read_exact(&mut data).ok()?;
+        if crc32(&data) != entry.checksum {
+            log_error!(
+                "{} in {} is damaged, checksum mismatch",
+                path,
+                ARCHIVE_FILE_PATH
+            );
+            return None;
+        }
+        Some(data)
+    }
+
+    pub fn entry_count(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+/// Writes archive from (path, data) pairs
+pub fn write_archive(path: &str, files: &[(String, Vec<u8>)]) -> io::Result<()> {
+    let toc_size: usize = files
+        .iter()
+        .map(|(file_path, _)| 2usize + file_path.len() + 8usize + 8usize + 4usize)
+        .sum();
+    let mut offset: u64 = (ARCHIVE_MAGIC.len() + 4usize + toc_size) as u64;
+
+    let mut archive: Vec<u8> = Vec::new();
+    archive.extend_from_slice(ARCHIVE_MAGIC);
+    archive.extend_from_slice(&(files.len() as u32).to_le_bytes());
+    for (file_path, data) in files {
+        let path_length: u16 = u16::try_from(file_path.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "path is too long"))?;
+        archive.extend_from_slice(&path_length.to_le_bytes());
+        archive.extend_from_slice(file_path.as_bytes());
+        archive.extend_from_slice(&offset.to_le_bytes());
+        archive.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        archive.extend_from_slice(&crc32(data).to_le_bytes());
+        offset += data.len() as u64;
+    }
+    for (_, data) in files {
+        archive.extend_from_slice(data);
+    }
+
+    File::create(path)?.write_all(&archive)
+}
+
+/// Reads file by its path relative to the game directory.
+/// Loose files win over the archive, so single assets can be replaced for modding
+pub fn read_file(path: &str) -> Option<Vec<u8>> {
+    if let Ok(data) = fs::read(path) {
+        return Some(data);
+    }
+    let archive: &Archive = ARCHIVE
+        .get_or_init(|| match Archive::open(ARCHIVE_FILE_PATH) {
+            Ok(archive) => {
+                log_info!(
+                    "Opened {} with {} files",
+                    ARCHIVE_FILE_PATH,
+                    archive.entry_count()
+                );
+                Some(archive)
+            }
+            Err(_) => None,
+        })
+        .as_ref()?;
+    archive.read(&normalize_path(path))
+}
+
+/// read_file() for text files
+pub fn read_file_string(path: &str) -> Option<String> {
+    String::from_utf8(read_file(path)?).ok()
+}
+
+/// Archive paths use "/" and have no leading "./"
+pub fn normalize_path(path: &str) -> String {
+    path.replace('\\', "/").trim_start_matches("./").to_string()
+}
+
+/// CRC-32 (IEEE), bitwise version is fast enough for game assets
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0xFFFF_FFFFu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask: u32 = (crc & 1u32).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320u32 & mask);
+        }
+    }
+    !crc
+}
+
+fn read_array<const N: usize>(file: &mut File) -> io::Result<[u8; N]> {
+    let mut bytes: [u8; N] = [0u8; N];
+    file.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    fn temp_path(name: &str) -> String {
+        env::temp_dir()
+            .join(format!("nmf_{}_{}.pak", name, process::id()))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    fn sample_files() -> Vec<(String, Vec<u8>)> {
+        vec![
+            ("empty.txt".to_string(), Vec::new()),
+            ("lang/en.txt".to_string(), b"menu.start = Start".to_vec()),
+            ("stages/stage_1.txt".to_string(), b"scroll 6".to_vec()),
+        ]
+    }
+
+    #[test]
+    fn packed_files_read_back() {
+        let path: String = temp_path("roundtrip");
+        write_archive(&path, &sample_files()).unwrap();
+        let archive: Archive = Archive::open(&path).unwrap();
+        assert_eq!(archive.entry_count(), 3usize);
+        for (file_path, data) in sample_files() {
+            assert_eq!(archive.read(&file_path), Some(data));
+        }
+        assert_eq!(archive.read("missing.txt"), None);
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn corrupted_entry_is_rejected() {
+        let path: String = temp_path("corrupted");
+        write_archive(&path, &sample_files()).unwrap();
+        let mut bytes: Vec<u8> = fs::read(&path).unwrap();
+        let data_start: usize = bytes.len() - "menu.start = Startscroll 6".len();
+        bytes[data_start] ^= 0xFFu8;
+        fs::write(&path, &bytes).unwrap();
+
+        let archive: Archive = Archive::open(&path).unwrap();
+        assert_eq!(archive.read("lang/en.txt"), None);
+        assert_eq!(
+            archive.read("stages/stage_1.txt"),
+            Some(b"scroll 6".to_vec())
+        );
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn entries_past_the_end_are_skipped() {
+        let path: String = temp_path("truncated");
+        write_archive(&path, &sample_files()).unwrap();
+        let bytes: Vec<u8> = fs::read(&path).unwrap();
+        // Last bytes of the last file are cut off
+        fs::write(&path, &bytes[..bytes.len() - 2usize]).unwrap();
+
+        let archive: Archive = Archive::open(&path).unwrap();
+        assert_eq!(archive.entry_count(), 2usize);
+        assert_eq!(archive.read("stages/stage_1.txt"), None);
+        assert!(archive.read("lang/en.txt").is_some());
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn other_files_are_not_archives() {
+        let path: String = temp_path("not_archive");
+        fs::write(&path, b"NMFPAK").unwrap();
+        assert!(Archive::open(&path).is_err());
+        fs::write(&path, b"PK\x03\x04 something else").unwrap();
+        assert!(Archive::open(&path).is_err());
+        fs::remove_file(&path).ok();
+    }
+}
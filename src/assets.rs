@@ -0,0 +1,438 @@
+use raylib::prelude::*;
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::ops::Deref;
+use std::path::Path;
+
+use crate::archive::*;
+use crate::cmap::*;
+use crate::global::*;
+
+const MANIFEST_FILE_PATH: &str = "assets/manifest.txt";
+pub const TRACKS_FILE_PATH: &str = "bgm/tracks.txt";
+
+// ASSET IDS
+pub const MAIN_FONT_ID: &str = "main";
+pub const FALLBACK_FONT_ID: &str = "fallback";
+pub const MENU_BGM_ID: &str = "menu_bgm";
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum AssetKind {
+    Texture,
+    Font,
+    Music,
+    Sound,
+    Shader,
+}
+
+impl AssetKind {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "texture" => Some(AssetKind::Texture),
+            "font" => Some(AssetKind::Font),
+            "music" => Some(AssetKind::Music),
+            "sound" => Some(AssetKind::Sound),
+            "shader" => Some(AssetKind::Shader),
+            _ => None,
+        }
+    }
+}
+
+/// Music streamed from memory, keeps file data alive while it plays
+pub struct MusicStream<'aud> {
+    music: Music<'aud>,
+    // Read by the decoder while streaming, so it is dropped after the music
+    _data: Vec<u8>,
+}
+
+impl<'aud> Deref for MusicStream<'aud> {
+    type Target = Music<'aud>;
+
+    fn deref(&self) -> &Music<'aud> {
+        &self.music
+    }
+}
+
+/// Loads assets by logical id from the manifest and keeps them cached.
+/// Files are read loose or from the archive, missing ones are replaced with placeholders
+pub struct AssetManager<'aud> {
+    paths: HashMap<(AssetKind, String), String>,
+    textures: HashMap<String, Texture2D>,
+    fonts: HashMap<String, Font>,
+    music: HashMap<String, MusicStream<'aud>>,
+    sounds: HashMap<String, Sound<'aud>>,
+    // Placeholders
+    checkerboard: Texture2D,
+    default_font: Font,
+}
+
+impl<'aud> AssetManager<'aud> {
+    const FONT_SIZE: i32 = 84i32;
+
+    pub fn new(rl: &mut RaylibHandle, thread: &RaylibThread) -> Self {
+        let checkerboard_image: Image =
+            Image::gen_image_checked(64i32, 64i32, 8i32, 8i32, Color::MAGENTA, Color::BLACK);
+        Self {
+            paths: load_manifest(),
+            textures: HashMap::new(),
+            fonts: HashMap::new(),
+            music: HashMap::new(),
+            sounds: HashMap::new(),
+            checkerboard: rl
+                .load_texture_from_image(thread, &checkerboard_image)
+                .unwrap(),
+            default_font: rl
+                .load_font_from_memory(thread, ".ttf", MAIN_FONT, Self::FONT_SIZE, None)
+                .unwrap(),
+        }
+    }
+
+    /// Returns ids of the assets that the manifest maps to the path
+    pub fn ids_of_path(&self, kind: AssetKind, path: &Path) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .paths
+            .iter()
+            .filter(|((asset_kind, _), asset_path)| {
+                *asset_kind == kind && Path::new(asset_path.as_str()) == path
+            })
+            .map(|((_, id), _)| id.clone())
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// Returns manifest path of an asset
+    pub fn path(&self, kind: AssetKind, id: &str) -> Option<&str> {
+        let path: Option<&str> = self
+            .paths
+            .get(&(kind, id.to_string()))
+            .map(|path| path.as_str());
+        if path.is_none() {
+            log_warn!("Asset {:?} '{}' is not in {}", kind, id, MANIFEST_FILE_PATH);
+        }
+        path
+    }
+
+    pub fn load_texture(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, id: &str) {
+        if self.textures.contains_key(id) {
+            return;
+        }
+        let path: String = match self.path(AssetKind::Texture, id) {
+            Some(path) => path.to_string(),
+            None => return,
+        };
+        match load_texture_file(rl, thread, &path) {
+            Some(texture) => {
+                log_trace!("Texture '{}' loaded from {}", id, path);
+                self.textures.insert(id.to_string(), texture);
+            }
+            None => log_warn!("Texture {} failed to load, using placeholder", path),
+        }
+    }
+
+    /// Loads font with glyphs for ASCII and every character of glyphs, replacing the cached one.
+    /// Returns false if the font file has no glyphs for some of them, nothing is loaded then
+    pub fn load_font(&mut self, thread: &RaylibThread, id: &str, glyphs: &str) -> bool {
+        let path: String = match self.path(AssetKind::Font, id) {
+            Some(path) => path.to_string(),
+            None => return false,
+        };
+        let data: Vec<u8> = match read_file(&path) {
+            Some(data) => data,
+            None => {
+                log_warn!("Font {} is missing, using built-in font", path);
+                return false;
+            }
+        };
+
+        let mut codepoints: Vec<char> = (' '..='~').chain(glyphs.chars()).collect();
+        codepoints.retain(|c| !c.is_control());
+        codepoints.sort();
+        codepoints.dedup();
+        match font_codepoints(&data) {
+            Some(covered) => {
+                let missing: String = codepoints
+                    .iter()
+                    .filter(|c| !c.is_whitespace() && !covered.contains(&(**c as u32)))
+                    .collect();
+                if !missing.is_empty() {
+                    log_warn!("Font {} has no glyphs for '{}'", path, missing);
+                    return false;
+                }
+            }
+            None => log_warn!(
+                "Can't read glyph table of {}, assuming it is complete",
+                path
+            ),
+        }
+
+        match load_font_codepoints(thread, &data, &file_type(&path), &codepoints) {
+            Some(font) => {
+                self.fonts.insert(id.to_string(), font);
+                true
+            }
+            None => {
+                log_warn!("Font {} failed to load, using built-in font", path);
+                false
+            }
+        }
+    }
+
+    pub fn load_music(&mut self, audio: &'aud RaylibAudio, id: &str) {
+        if self.music.contains_key(id) {
+            return;
+        }
+        let loaded: Option<MusicStream<'aud>> =
+            self.path(AssetKind::Music, id)
+                .and_then(|path| match load_music_file(audio, path) {
+                    Some(music) => Some(music),
+                    None => {
+                        log_warn!("Music {} failed to load, using silence", path);
+                        None
+                    }
+                });
+        let music: Option<MusicStream<'aud>> = loaded.or_else(|| {
+            let silence: Vec<u8> = silent_wav();
+            let music: Music = audio.new_music_from_memory(".wav", &silence).ok()?;
+            Some(MusicStream {
+                music,
+                _data: silence,
+            })
+        });
+        if let Some(music) = music {
+            self.music.insert(id.to_string(), music);
+        }
+    }
+
+    pub fn load_sound(&mut self, audio: &'aud RaylibAudio, id: &str) {
+        if self.sounds.contains_key(id) {
+            return;
+        }
+        let loaded: Option<Sound<'aud>> =
+            self.path(AssetKind::Sound, id)
+                .and_then(|path| match load_sound_file(audio, path) {
+                    Some(sound) => Some(sound),
+                    None => {
+                        log_warn!("Sound {} failed to load, using silence", path);
+                        None
+                    }
+                });
+        let sound: Option<Sound<'aud>> = loaded.or_else(|| {
+            let wave: Wave = audio.new_wave_from_memory(".wav", &silent_wav()).ok()?;
+            audio.new_sound_from_wave(&wave).ok()
+        });
+        if let Some(sound) = sound {
+            self.sounds.insert(id.to_string(), sound);
+        }
+    }
+
+    /// Returns texture, checkerboard if it is missing
+    pub fn texture(&self, id: &str) -> &Texture2D {
+        self.textures.get(id).unwrap_or(&self.checkerboard)
+    }
+
+    /// Returns font, embedded main font if it is missing
+    pub fn font(&self, id: &str) -> &Font {
+        self.fonts.get(id).unwrap_or(&self.default_font)
+    }
+
+    /// Returns music, silent one if file is missing. None only if it was never loaded
+    pub fn music(&self, id: &str) -> Option<&Music<'aud>> {
+        self.music.get(id).map(|music| &music.music)
+    }
+
+    /// Returns sound, silent one if file is missing. None only if it was never loaded
+    pub fn sound(&self, id: &str) -> Option<&Sound<'aud>> {
+        self.sounds.get(id)
+    }
+}
+
+/// Loop points of a music track in seconds
+#[derive(Clone, Copy)]
+pub struct TrackInfo {
+    pub loop_start: f32,
+    pub loop_end: f32,
+}
+
+/// Track metadata read from TRACKS_FILE_PATH, tracks without it play through and start over
+pub struct TrackList {
+    tracks: HashMap<String, TrackInfo>,
+    // Lines that failed to parse, shown by the hot reload overlay
+    pub errors: Vec<String>,
+}
+
+impl TrackList {
+    /// Line format: music_id loop_start loop_end, "#" starts a comment
+    pub fn load() -> Self {
+        let mut list: TrackList = Self {
+            tracks: HashMap::new(),
+            errors: Vec::new(),
+        };
+        let data: String = match read_file_string(TRACKS_FILE_PATH) {
+            Some(data) => data,
+            None => {
+                log_warn!("{} is missing, music has no loop points", TRACKS_FILE_PATH);
+                return list;
+            }
+        };
+
+        for (line_number, line) in data.lines().enumerate() {
+            let line: &str = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let track: Option<TrackInfo> = match words.as_slice() {
+                [_, loop_start, loop_end] => match (loop_start.parse(), loop_end.parse()) {
+                    (Ok(loop_start), Ok(loop_end)) if loop_start < loop_end => Some(TrackInfo {
+                        loop_start,
+                        loop_end,
+                    }),
+                    _ => None,
+                },
+                _ => None,
+            };
+            match track {
+                Some(track) => {
+                    list.tracks.insert(words[0].to_string(), track);
+                }
+                None => {
+                    let error: String = format!(
+                        "{}:{}: can't parse '{}'",
+                        TRACKS_FILE_PATH,
+                        line_number + 1,
+                        line
+                    );
+                    log_error!("{}", error);
+                    list.errors.push(error);
+                }
+            }
+        }
+        list
+    }
+
+    pub fn get(&self, music_id: &str) -> Option<TrackInfo> {
+        self.tracks.get(music_id).copied()
+    }
+}
+
+/// Loads texture from a loose file or the archive
+pub fn load_texture_file(
+    rl: &mut RaylibHandle,
+    thread: &RaylibThread,
+    path: &str,
+) -> Option<Texture2D> {
+    let data: Vec<u8> = read_file(path)?;
+    let image: Image = Image::load_image_from_mem(&file_type(path), &data).ok()?;
+    rl.load_texture_from_image(thread, &image).ok()
+}
+
+/// Loads font with the given glyphs from file data.
+/// raylib-rs sizes its codepoint buffer by bytes of the text, which breaks on
+/// non-ASCII glyphs, so the codepoints are passed to raylib directly
+fn load_font_codepoints(
+    _: &RaylibThread,
+    data: &[u8],
+    file_type: &str,
+    codepoints: &[char],
+) -> Option<Font> {
+    let file_type: CString = CString::new(file_type).ok()?;
+    let mut codepoints: Vec<i32> = codepoints.iter().map(|c| *c as i32).collect();
+    let font: ffi::Font = unsafe {
+        ffi::LoadFontFromMemory(
+            file_type.as_ptr(),
+            data.as_ptr(),
+            data.len() as i32,
+            AssetManager::FONT_SIZE,
+            codepoints.as_mut_ptr(),
+            codepoints.len() as i32,
+        )
+    };
+    if font.glyphs.is_null() || font.texture.id == 0 {
+        return None;
+    }
+    // Font now owns the raylib data and unloads it on drop
+    Some(unsafe { Font::from_raw(font) })
+}
+
+/// Loads music stream from a loose file or the archive
+pub fn load_music_file<'aud>(audio: &'aud RaylibAudio, path: &str) -> Option<MusicStream<'aud>> {
+    let data: Vec<u8> = read_file(path)?;
+    let music: Music = audio.new_music_from_memory(&file_type(path), &data).ok()?;
+    Some(MusicStream { music, _data: data })
+}
+
+/// Loads sound from a loose file or the archive
+pub fn load_sound_file<'aud>(audio: &'aud RaylibAudio, path: &str) -> Option<Sound<'aud>> {
+    let data: Vec<u8> = read_file(path)?;
+    let wave: Wave = audio.new_wave_from_memory(&file_type(path), &data).ok()?;
+    audio.new_sound_from_wave(&wave).ok()
+}
+
+/// Extension with a dot, raylib picks decoders by it
+fn file_type(path: &str) -> String {
+    Path::new(path)
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy().to_lowercase()))
+        .unwrap_or_default()
+}
+
+/// Reads manifest, line format: kind id path
+fn load_manifest() -> HashMap<(AssetKind, String), String> {
+    let mut paths: HashMap<(AssetKind, String), String> = HashMap::new();
+    let data: String = match read_file_string(MANIFEST_FILE_PATH) {
+        Some(data) => data,
+        None => {
+            log_warn!(
+                "{} is missing, every asset is a placeholder",
+                MANIFEST_FILE_PATH
+            );
+            return paths;
+        }
+    };
+
+    for line in data.lines() {
+        let line: &str = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut words = line.splitn(3, char::is_whitespace);
+        match (
+            words.next().and_then(AssetKind::from_name),
+            words.next(),
+            words.next(),
+        ) {
+            (Some(kind), Some(id), Some(path)) => {
+                paths.insert((kind, id.to_string()), path.trim().to_string());
+            }
+            _ => log_warn!(
+                "Skipping malformed line in {}: '{}'",
+                MANIFEST_FILE_PATH,
+                line
+            ),
+        }
+    }
+    paths
+}
+
+/// Half a second of 16 bit mono silence as a WAV file
+fn silent_wav() -> Vec<u8> {
+    const SAMPLE_RATE: u32 = 22050u32;
+    const DATA_SIZE: u32 = SAMPLE_RATE; // 2 bytes per sample
+
+    let mut wav: Vec<u8> = Vec::new();
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36u32 + DATA_SIZE).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes()); // fmt chunk size
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes()); // mono
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SAMPLE_RATE * 2u32).to_le_bytes()); // byte rate
+    wav.extend_from_slice(&2u16.to_le_bytes()); // block align
+    wav.extend_from_slice(&16u16.to_le_bytes()); // bits per sample
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&DATA_SIZE.to_le_bytes());
+    wav.resize(wav.len() + DATA_SIZE as usize, 0u8);
+    wav
+}
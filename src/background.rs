@@ -0,0 +1,247 @@
+use raylib::prelude::*;
+
+use crate::shaders::*;
+use crate::stage_script::*;
+
+/// 3D shape of a background segment, positions are local to the segment start
+#[derive(Clone, Copy)]
+pub enum Primitive {
+    Cube {
+        position: Vector3,
+        size: Vector3,
+        color: Color,
+    },
+    Plane {
+        position: Vector3,
+        size: Vector2,
+        color: Color,
+    },
+    Cylinder {
+        position: Vector3,
+        radius: f32,
+        height: f32,
+        color: Color,
+    },
+    Sphere {
+        position: Vector3,
+        radius: f32,
+        color: Color,
+    },
+}
+
+/// Piece of scenery that is repeated along the scroll direction
+pub struct Segment {
+    pub length: f32,
+    pub primitives: Vec<Primitive>,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Easing {
+    Linear,
+    In,
+    Out,
+    InOut,
+}
+
+impl Easing {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "linear" => Some(Easing::Linear),
+            "in" => Some(Easing::In),
+            "out" => Some(Easing::Out),
+            "inout" => Some(Easing::InOut),
+            _ => None,
+        }
+    }
+
+    /// Maps linear progress 0..1 to eased progress 0..1 (cubic curves)
+    pub fn apply(&self, t: f32) -> f32 {
+        let t: f32 = t.clamp(0f32, 1f32);
+        match self {
+            Easing::Linear => t,
+            Easing::In => t * t * t,
+            Easing::Out => 1f32 - (1f32 - t).powi(3),
+            Easing::InOut => {
+                if t < 0.5f32 {
+                    4f32 * t * t * t
+                } else {
+                    1f32 - (-2f32 * t + 2f32).powi(3) / 2f32
+                }
+            }
+        }
+    }
+}
+
+/// Camera state at a point of stage time, easing is used on the way to this key
+#[derive(Clone, Copy)]
+pub struct CameraKeyframe {
+    pub time: f32,
+    pub position: Vector3,
+    pub target: Vector3,
+    pub fovy: f32,
+    pub easing: Easing,
+}
+
+/// Streams stage segments past the camera and moves camera along its path
+pub struct Background {
+    time: f32,
+    scroll: f32,
+}
+
+impl Background {
+    // How far segments are drawn in front of and behind the camera
+    const VIEW_DISTANCE: f32 = 80f32;
+    const BEHIND_DISTANCE: f32 = 10f32;
+    const CYLINDER_SLICES: i32 = 12i32;
+
+    pub fn new() -> Self {
+        Self {
+            time: 0f32,
+            scroll: 0f32,
+        }
+    }
+
+    /// Starts stage background from the beginning
+    pub fn reset(&mut self) {
+        self.time = 0f32;
+        self.scroll = 0f32;
+    }
+
+    /// Seconds since the stage started
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Distance the segments moved since the stage started
+    pub fn scroll(&self) -> f32 {
+        self.scroll
+    }
+
+    /// Goes back to the start of the segment the camera is over, stage time goes back with it
+    pub fn restart_segment(&mut self, script: &StageScript) {
+        let cycle_length: f32 = script.segments.iter().map(|segment| segment.length).sum();
+        if cycle_length <= 0f32 {
+            return;
+        }
+        let position: f32 = self.scroll % cycle_length;
+        let mut start: f32 = 0f32;
+        for segment in &script.segments {
+            if start + segment.length > position {
+                break;
+            }
+            start += segment.length;
+        }
+        let rewind: f32 = position - start;
+        self.scroll -= rewind;
+        if script.scroll_speed > 0f32 {
+            self.time = (self.time - rewind / script.scroll_speed).max(0f32);
+        }
+    }
+
+    pub fn update(&mut self, delta_time: &f32, script: &StageScript) {
+        self.time += delta_time;
+        self.scroll += script.scroll_speed * delta_time;
+    }
+
+    /// Returns camera for current stage time, fallback is used when path is empty
+    pub fn camera(&self, script: &StageScript, fallback: &Camera3D) -> Camera3D {
+        camera_on_path(&script.camera_path, self.time).unwrap_or(*fallback)
+    }
+
+    /// Draws segments in 3D mode, with fog if fog shader is loaded
+    pub fn draw(
+        &self,
+        d: &mut (impl RaylibDraw + RaylibDraw3D),
+        script: &StageScript,
+        shaders: &mut ShaderManager,
+    ) {
+        match shaders.get_mut(FOG_SHADER) {
+            Some(fog_shader) => {
+                let mut d = d.begin_shader_mode(fog_shader);
+                self.draw_segments(&mut d, script);
+            }
+            None => self.draw_segments(d, script),
+        }
+    }
+
+    fn draw_segments(&self, d: &mut impl RaylibDraw3D, script: &StageScript) {
+        let cycle_length: f32 = script.segments.iter().map(|segment| segment.length).sum();
+        if cycle_length <= 0f32 {
+            return;
+        }
+
+        // Segments go along -Z and move towards the camera while scrolling,
+        // one cycle back covers the segments already behind the camera
+        let mut start: f32 = -(self.scroll % cycle_length) - cycle_length;
+        let mut index: usize = 0usize;
+        while start < Self::VIEW_DISTANCE {
+            let segment: &Segment = &script.segments[index % script.segments.len()];
+            if start + segment.length > -Self::BEHIND_DISTANCE {
+                for primitive in &segment.primitives {
+                    self.draw_primitive(d, primitive, -start);
+                }
+            }
+            start += segment.length;
+            index += 1;
+        }
+    }
+
+    fn draw_primitive(&self, d: &mut impl RaylibDraw3D, primitive: &Primitive, offset_z: f32) {
+        let place =
+            |position: &Vector3| Vector3::new(position.x, position.y, offset_z - position.z);
+        match primitive {
+            Primitive::Cube {
+                position,
+                size,
+                color,
+            } => d.draw_cube(place(position), size.x, size.y, size.z, *color),
+            Primitive::Plane {
+                position,
+                size,
+                color,
+            } => d.draw_plane(place(position), *size, *color),
+            Primitive::Cylinder {
+                position,
+                radius,
+                height,
+                color,
+            } => d.draw_cylinder(
+                place(position),
+                *radius,
+                *radius,
+                *height,
+                Self::CYLINDER_SLICES,
+                *color,
+            ),
+            Primitive::Sphere {
+                position,
+                radius,
+                color,
+            } => d.draw_sphere(place(position), *radius, *color),
+        }
+    }
+}
+
+/// Interpolates camera between keyframes, holds first and last key outside of the path
+pub fn camera_on_path(path: &[CameraKeyframe], time: f32) -> Option<Camera3D> {
+    let first: &CameraKeyframe = path.first()?;
+    let (from, to) = match path.iter().position(|key| key.time > time) {
+        Some(0) => (first, first),
+        Some(next) => (&path[next - 1], &path[next]),
+        None => (path.last()?, path.last()?),
+    };
+
+    let span: f32 = to.time - from.time;
+    let progress: f32 = if span > 0f32 {
+        to.easing.apply((time - from.time) / span)
+    } else {
+        1f32
+    };
+
+    Some(Camera3D::perspective(
+        from.position.lerp(to.position, progress),
+        from.target.lerp(to.target, progress),
+        Vector3::new(0f32, 1f32, 0f32),
+        from.fovy + (to.fovy - from.fovy) * progress,
+    ))
+}
@@ -0,0 +1,77 @@
+//! Packs game assets into a single archive for release builds
+//!
+//! Usage: nmf_pack [output], run from the game directory. Output defaults to nmf.pak
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use nmf::archive::*;
+
+// Directories that go into the archive
+const PACKED_DIRS: [&str; 7] = ["assets", "bgm", "sfx", "shaders", "fonts", "lang", "stages"];
+// Sources that the game never loads
+const SKIPPED_EXTENSIONS: [&str; 2] = ["mscz", "mscz~"];
+
+fn main() {
+    let output: String = env::args()
+        .nth(1)
+        .unwrap_or_else(|| ARCHIVE_FILE_PATH.to_string());
+
+    let mut files: Vec<(String, Vec<u8>)> = Vec::new();
+    for dir in PACKED_DIRS {
+        collect_files(Path::new(dir), &mut files);
+    }
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let total_size: usize = files.iter().map(|(_, data)| data.len()).sum();
+    for (path, data) in &files {
+        println!("{:>10} {}", data.len(), path);
+    }
+
+    match write_archive(&output, &files) {
+        Ok(()) => println!(
+            "Packed {} files ({} bytes) into {}",
+            files.len(),
+            total_size,
+            output
+        ),
+        Err(err) => {
+            eprintln!("Failed to write {}: {}", output, err);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Walks directory recursively, hidden files and sources are skipped
+fn collect_files(dir: &Path, files: &mut Vec<(String, Vec<u8>)>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => {
+            println!("Skipping {}, directory is missing", dir.display());
+            return;
+        }
+    };
+
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        let name: String = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+        if path.is_dir() {
+            collect_files(&path, files);
+            continue;
+        }
+        let is_skipped: bool = path
+            .extension()
+            .is_some_and(|extension| SKIPPED_EXTENSIONS.contains(&&*extension.to_string_lossy()));
+        if is_skipped {
+            continue;
+        }
+        match fs::read(&path) {
+            Ok(data) => files.push((normalize_path(&path.to_string_lossy()), data)),
+            Err(err) => println!("Skipping {}: {}", path.display(), err),
+        }
+    }
+}
@@ -0,0 +1,259 @@
+//! Runs gameplay without a window, audio or GPU, for automated tests on CI
+//!
+//! Usage: nmf_sim [--frames <n>] [--stage <1..7>] [--difficulty <name>] [--seed <n>]
+//!                [--replay <path> | --input <path>] [--record <path> | --expect <path>],
+//!                run from the game directory.
+//! Simulation stops early when the run is over. Prints frames, score, deaths, how the run
+//! ended and a hash of the final state, one per line.
+//! State hash is taken after every frame, --record writes these checkpoints and --expect
+//! fails with exit code 1 on the first one that differs, which is the exact diverging frame
+
+use std::env;
+use std::fs;
+use std::process;
+
+use nmf::background::*;
+use nmf::log::*;
+use nmf::player::*;
+use nmf::replay::*;
+use nmf::run::*;
+use nmf::simulation::*;
+use nmf::stage_script::*;
+
+// Frames simulated when neither --frames nor --replay says how many
+const DEFAULT_FRAMES: u64 = 3600u64;
+
+/// Where inputs come from, frames after the end get no input
+enum InputSource {
+    Idle,
+    Replay(ReplayPlayer),
+    // One input byte per frame
+    Script(Vec<u8>),
+}
+
+fn main() {
+    let mut frames: Option<u64> = None;
+    let mut stage: Option<u8> = None;
+    let mut difficulty: Difficulty = Difficulty::Normal;
+    let mut seed: u64 = 0u64;
+    let mut input_source: InputSource = InputSource::Idle;
+    let mut record_path: Option<String> = None;
+    let mut expect_path: Option<String> = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let value: String = args
+            .next()
+            .unwrap_or_else(|| fail(&format!("{} needs a value", flag)));
+        match flag.as_str() {
+            "--frames" => frames = Some(parse_value(&flag, &value)),
+            "--stage" => stage = Some(parse_value(&flag, &value)),
+            "--difficulty" => {
+                difficulty = Difficulty::from_name(&value)
+                    .unwrap_or_else(|| fail(&format!("Unknown difficulty '{}'", value)))
+            }
+            "--seed" => seed = parse_value(&flag, &value),
+            "--replay" => {
+                let replay: Replay = Replay::load(&value).unwrap_or_else(|err| fail(&err));
+                input_source = InputSource::Replay(ReplayPlayer::new(replay));
+            }
+            "--input" => {
+                input_source =
+                    InputSource::Script(load_input_script(&value).unwrap_or_else(|err| fail(&err)))
+            }
+            "--record" => record_path = Some(value),
+            "--expect" => expect_path = Some(value),
+            _ => fail(&format!("Unknown flag '{}'", flag)),
+        }
+    }
+    init_log(LogFilter::new(LogLevel::Warn), None);
+
+    // A replay decides where the run starts, flags do otherwise
+    let mut run: RunData = match &input_source {
+        InputSource::Replay(replay_player) => replay_player.run(),
+        _ => RunData::with_seed(
+            difficulty,
+            stage.unwrap_or_else(|| start_stage(difficulty)),
+            seed,
+        ),
+    };
+    let mut stage_script: StageScript = StageScript::load(run.stage);
+    let mut background: Background = Background::new();
+    let mut player: Player = Player::new();
+
+    let max_frames: u64 = frames.unwrap_or(match input_source {
+        InputSource::Replay(_) => u64::MAX,
+        _ => DEFAULT_FRAMES,
+    });
+    let mut frame: u64 = 0u64;
+    let mut outcome: StepOutcome = StepOutcome::Playing;
+    // Frame and state hash
+    let mut checkpoints: Vec<(u64, u64)> = Vec::new();
+    while frame < max_frames {
+        let input: u8 = match &mut input_source {
+            InputSource::Idle => 0u8,
+            InputSource::Replay(replay_player) => match replay_player.next_input() {
+                Some(input) => input,
+                None => break,
+            },
+            InputSource::Script(inputs) => inputs.get(frame as usize).copied().unwrap_or(0u8),
+        };
+        frame += 1u64;
+        set_log_frame(frame);
+        // No textures without GPU, so the player has no atlas
+        outcome = step_gameplay(
+            input,
+            &mut run,
+            &mut stage_script,
+            &mut background,
+            &mut player,
+            None,
+        );
+        checkpoints.push((frame, gameplay_hash(&run, &player, &background)));
+        if outcome != StepOutcome::Playing {
+            break;
+        }
+    }
+
+    println!("frames {}", frame);
+    println!("stage {}", run.stage);
+    println!("score {}", run.score);
+    println!("deaths {}", run.misses);
+    println!("bombs {}", run.bombs_used);
+    println!("outcome {}", outcome.as_str());
+    println!("hash {:016x}", gameplay_hash(&run, &player, &background));
+
+    if let Some(record_path) = &record_path {
+        if let Err(err) = save_checkpoints(record_path, &checkpoints) {
+            fail(&err);
+        }
+    }
+    if let Some(expect_path) = &expect_path {
+        let expected: Vec<(u64, u64)> =
+            load_checkpoints(expect_path).unwrap_or_else(|err| fail(&err));
+        if let Err(err) = compare_checkpoints(&expected, &checkpoints) {
+            eprintln!("{}: {}", expect_path, err);
+            process::exit(1);
+        }
+    }
+}
+
+/// Line per checkpoint: "<frame> <hash in hex>"
+fn save_checkpoints(path: &str, checkpoints: &[(u64, u64)]) -> Result<(), String> {
+    let data: String = checkpoints
+        .iter()
+        .map(|(frame, hash)| format!("{} {:016x}\n", frame, hash))
+        .collect();
+    fs::write(path, data.as_bytes()).map_err(|err| format!("{}: {}", path, err))
+}
+
+fn load_checkpoints(path: &str) -> Result<Vec<(u64, u64)>, String> {
+    let data: String = fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+    let mut checkpoints: Vec<(u64, u64)> = Vec::new();
+    for (line_number, line) in data.lines().enumerate() {
+        let line: &str = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let checkpoint: Option<(u64, u64)> = line.split_once(' ').and_then(|(frame, hash)| {
+            Some((
+                frame.parse().ok()?,
+                u64::from_str_radix(hash.trim(), 16).ok()?,
+            ))
+        });
+        match checkpoint {
+            Some(checkpoint) => checkpoints.push(checkpoint),
+            None => {
+                return Err(format!(
+                    "{}:{}: can't parse '{}'",
+                    path,
+                    line_number + 1,
+                    line
+                ))
+            }
+        }
+    }
+    Ok(checkpoints)
+}
+
+/// Error names the first checkpoint that differs and the last one that matched
+fn compare_checkpoints(expected: &[(u64, u64)], actual: &[(u64, u64)]) -> Result<(), String> {
+    let mut last_match: u64 = 0u64;
+    for (index, (frame, hash)) in expected.iter().enumerate() {
+        match actual.get(index) {
+            Some((actual_frame, actual_hash)) if actual_frame == frame && actual_hash == hash => {
+                last_match = *frame;
+            }
+            Some((actual_frame, actual_hash)) if actual_frame == frame => {
+                return Err(format!(
+                    "diverged at frame {}, hash {:016x} instead of {:016x}, frame {} still matched",
+                    frame, actual_hash, hash, last_match
+                ));
+            }
+            Some((actual_frame, _)) => {
+                return Err(format!(
+                    "checkpoint at frame {} instead of {}, frame {} still matched",
+                    actual_frame, frame, last_match
+                ));
+            }
+            None => {
+                return Err(format!(
+                    "run ended before frame {}, frame {} still matched",
+                    frame, last_match
+                ));
+            }
+        }
+    }
+    match actual.get(expected.len()) {
+        Some((frame, _)) => Err(format!(
+            "run went on to frame {}, past the last checkpoint",
+            frame
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Script lines are "<frames> [up] [down] [left] [right] [attack] [bomb] [slow]",
+/// inputs are held for that many frames, "#" starts a comment
+fn load_input_script(path: &str) -> Result<Vec<u8>, String> {
+    let data: String = fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+    let mut inputs: Vec<u8> = Vec::new();
+    for (line_number, line) in data.lines().enumerate() {
+        let line: &str = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let error = || format!("{}:{}: can't parse '{}'", path, line_number + 1, line);
+        let mut words = line.split_whitespace();
+        let count: usize = words
+            .next()
+            .and_then(|count| count.parse().ok())
+            .ok_or_else(error)?;
+        let mut input: u8 = 0u8;
+        for word in words {
+            input |= match word {
+                "up" => INPUT_UP,
+                "down" => INPUT_DOWN,
+                "left" => INPUT_LEFT,
+                "right" => INPUT_RIGHT,
+                "attack" => INPUT_ATTACK,
+                "bomb" => INPUT_BOMB,
+                "slow" => INPUT_SLOW,
+                _ => return Err(error()),
+            };
+        }
+        inputs.extend(std::iter::repeat_n(input, count));
+    }
+    Ok(inputs)
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: &str) -> T {
+    value
+        .parse()
+        .unwrap_or_else(|_| fail(&format!("{}: can't parse '{}'", flag, value)))
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(2);
+}
@@ -0,0 +1,236 @@
+use crate::global::*;
+use crate::log::*;
+use crate::run::*;
+
+/// Command line flag, "value" names its argument for flags that take one
+struct Flag {
+    name: &'static str,
+    value: Option<&'static str>,
+    help: &'static str,
+}
+
+const FLAGS: [Flag; 10] = [
+    Flag {
+        name: "--config",
+        value: Some("<path>"),
+        help: "Read and save options in this file instead of options.dat",
+    },
+    Flag {
+        name: "--data-dir",
+        value: Some("<path>"),
+        help: "Directory with game data, relative paths start there",
+    },
+    Flag {
+        name: "--windowed",
+        value: None,
+        help: "Start in a window",
+    },
+    Flag {
+        name: "--fullscreen",
+        value: None,
+        help: "Start in exclusive fullscreen",
+    },
+    Flag {
+        name: "--skip-intro",
+        value: None,
+        help: "Skip the greeting screen",
+    },
+    Flag {
+        name: "--stage",
+        value: Some("<1..7>"),
+        help: "Start playing this stage",
+    },
+    Flag {
+        name: "--difficulty",
+        value: Some("<name>"),
+        help: "Difficulty of --stage: easy, normal, hard, lunatic or extra",
+    },
+    Flag {
+        name: "--replay",
+        value: Some("<path>"),
+        help: "Play a replay file",
+    },
+    Flag {
+        name: "--log-level",
+        value: Some("<filter>"),
+        help: "error, warn, info, debug, trace or off, per module as info,assets=debug",
+    },
+    Flag {
+        name: "--help",
+        value: None,
+        help: "Show this text",
+    },
+];
+
+/// What was asked for on the command line, None means options decide
+pub struct LaunchOptions {
+    pub config_path: Option<String>,
+    pub data_dir: Option<String>,
+    pub window_mode: Option<WindowMode>,
+    pub skip_intro: bool,
+    pub stage: Option<u8>,
+    pub difficulty: Option<Difficulty>,
+    pub replay_path: Option<String>,
+    pub log_filter: LogFilter,
+    pub show_help: bool,
+}
+
+impl LaunchOptions {
+    /// Parses arguments without the program name
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options: LaunchOptions = Self {
+            config_path: None,
+            data_dir: None,
+            window_mode: None,
+            skip_intro: false,
+            stage: None,
+            difficulty: None,
+            replay_path: None,
+            log_filter: LogFilter::new(LogLevel::Info),
+            show_help: false,
+        };
+
+        while let Some(arg) = args.next() {
+            // "--flag=value" works as well as "--flag value"
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                None => (arg.clone(), None),
+            };
+            let flag: &Flag = FLAGS
+                .iter()
+                .find(|flag| flag.name == name)
+                .ok_or_else(|| format!("Unknown flag '{}'", arg))?;
+            let value: String = match flag.value {
+                Some(placeholder) => match inline_value.or_else(|| args.next()) {
+                    Some(value) => value,
+                    None => return Err(format!("{} needs {}", flag.name, placeholder)),
+                },
+                None if inline_value.is_some() => {
+                    return Err(format!("{} doesn't take a value", flag.name))
+                }
+                None => String::new(),
+            };
+
+            match flag.name {
+                "--config" => options.config_path = Some(value),
+                "--data-dir" => options.data_dir = Some(value),
+                "--windowed" => options.window_mode = Some(WindowMode::Windowed),
+                "--fullscreen" => options.window_mode = Some(WindowMode::Fullscreen),
+                "--skip-intro" => options.skip_intro = true,
+                "--stage" => {
+                    let stage: u8 = value
+                        .parse()
+                        .ok()
+                        .filter(|stage| (1u8..=7u8).contains(stage))
+                        .ok_or_else(|| format!("Stage '{}' does not exist", value))?;
+                    options.stage = Some(stage);
+                }
+                "--difficulty" => {
+                    options.difficulty = Some(
+                        Difficulty::from_name(&value)
+                            .ok_or_else(|| format!("Unknown difficulty '{}'", value))?,
+                    );
+                }
+                "--replay" => options.replay_path = Some(value),
+                "--log-level" => options.log_filter = LogFilter::parse(&value)?,
+                "--help" => options.show_help = true,
+                _ => unreachable!("Flag {} is defined but not handled", flag.name),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Usage text made from the flag definitions
+pub fn help_text() -> String {
+    let columns: Vec<String> = FLAGS
+        .iter()
+        .map(|flag| match flag.value {
+            Some(value) => format!("{} {}", flag.name, value),
+            None => flag.name.to_string(),
+        })
+        .collect();
+    let width: usize = columns.iter().map(|column| column.len()).max().unwrap_or(0);
+
+    let mut text: String = String::from("Usage: nmf [flags]\n\nFlags:\n");
+    for (column, flag) in columns.iter().zip(FLAGS.iter()) {
+        text.push_str(&format!(
+            "  {:width$}  {}\n",
+            column,
+            flag.help,
+            width = width
+        ));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<LaunchOptions, String> {
+        LaunchOptions::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn parse_error(args: &[&str]) -> String {
+        match parse(args) {
+            Ok(_) => panic!("{:?} was accepted", args),
+            Err(err) => err,
+        }
+    }
+
+    #[test]
+    fn values_go_after_space_or_equals() {
+        let launch: LaunchOptions = parse(&["--stage", "3", "--difficulty=hard"]).unwrap();
+        assert_eq!(launch.stage, Some(3u8));
+        assert_eq!(launch.difficulty, Some(Difficulty::Hard));
+        let launch: LaunchOptions = parse(&["--config=my=options.dat", "--windowed"]).unwrap();
+        assert_eq!(launch.config_path.as_deref(), Some("my=options.dat"));
+        assert_eq!(launch.window_mode, Some(WindowMode::Windowed));
+    }
+
+    #[test]
+    fn no_arguments_leave_everything_to_options() {
+        let launch: LaunchOptions = parse(&[]).unwrap();
+        assert!(launch.config_path.is_none() && launch.window_mode.is_none());
+        assert!(!launch.skip_intro && !launch.show_help);
+    }
+
+    #[test]
+    fn missing_value_is_rejected() {
+        assert_eq!(parse_error(&["--replay"]), "--replay needs <path>");
+    }
+
+    #[test]
+    fn value_of_valueless_flag_is_rejected() {
+        assert_eq!(
+            parse_error(&["--skip-intro=yes"]),
+            "--skip-intro doesn't take a value"
+        );
+    }
+
+    #[test]
+    fn stage_out_of_range_is_rejected() {
+        for stage in ["0", "8", "-1", "one"] {
+            assert_eq!(
+                parse_error(&["--stage", stage]),
+                format!("Stage '{}' does not exist", stage)
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_flag_is_rejected() {
+        assert_eq!(parse_error(&["--stages=2"]), "Unknown flag '--stages=2'");
+    }
+
+    #[test]
+    fn help_lists_every_flag() {
+        let text: String = help_text();
+        for flag in FLAGS.iter() {
+            assert!(text.contains(flag.name), "{} is missing", flag.name);
+            assert!(text.contains(flag.help), "help of {} is missing", flag.name);
+        }
+        assert!(text.contains("--stage <1..7>"));
+    }
+}
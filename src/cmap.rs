@@ -0,0 +1,194 @@
+use std::collections::HashSet;
+
+// Codepoints read from a single format 12 group, bigger groups are cut
+const MAX_GROUP_LENGTH: u32 = 0x10000u32;
+
+/// Reads character map of a TrueType/OpenType font and returns every codepoint
+/// it has a glyph for, None if the font can't be parsed.
+/// Only unicode subtables of format 4 (BMP) and 12 (full range) are read
+pub fn font_codepoints(data: &[u8]) -> Option<HashSet<u32>> {
+    let table_count: usize = read_u16(data, 4)? as usize;
+    let cmap: usize = (0..table_count).find_map(|i| {
+        let record: usize = 12usize + i * 16usize;
+        if data.get(record..record + 4)? == b"cmap" {
+            Some(read_u32(data, record + 8)? as usize)
+        } else {
+            None
+        }
+    })?;
+
+    let mut codepoints: HashSet<u32> = HashSet::new();
+    let subtable_count: usize = read_u16(data, cmap + 2)? as usize;
+    for i in 0..subtable_count {
+        let record: usize = cmap + 4usize + i * 8usize;
+        let platform: u16 = read_u16(data, record)?;
+        let encoding: u16 = read_u16(data, record + 2)?;
+        // Unicode platform or Windows unicode BMP / full repertoire
+        let is_unicode: bool =
+            platform == 0 || (platform == 3 && (encoding == 1 || encoding == 10));
+        if !is_unicode {
+            continue;
+        }
+        let subtable: usize = cmap + read_u32(data, record + 4)? as usize;
+        match read_u16(data, subtable)? {
+            4 => read_format_4(data, subtable, &mut codepoints)?,
+            12 => read_format_12(data, subtable, &mut codepoints)?,
+            _ => {}
+        }
+    }
+    Some(codepoints)
+}
+
+/// Segment mapping to delta values
+fn read_format_4(data: &[u8], subtable: usize, codepoints: &mut HashSet<u32>) -> Option<()> {
+    let segment_count: usize = read_u16(data, subtable + 6)? as usize / 2usize;
+    let end_codes: usize = subtable + 14usize;
+    let start_codes: usize = end_codes + segment_count * 2usize + 2usize;
+    let deltas: usize = start_codes + segment_count * 2usize;
+    let range_offsets: usize = deltas + segment_count * 2usize;
+
+    for segment in 0..segment_count {
+        let end: u16 = read_u16(data, end_codes + segment * 2usize)?;
+        let start: u16 = read_u16(data, start_codes + segment * 2usize)?;
+        let delta: u16 = read_u16(data, deltas + segment * 2usize)?;
+        let range_offset_position: usize = range_offsets + segment * 2usize;
+        let range_offset: usize = read_u16(data, range_offset_position)? as usize;
+        if start > end {
+            continue;
+        }
+        for code in start..=end {
+            // 0xFFFF closes the last segment and is not a character
+            if code == 0xFFFFu16 {
+                continue;
+            }
+            let glyph: u16 = if range_offset == 0 {
+                code.wrapping_add(delta)
+            } else {
+                let glyph_position: usize =
+                    range_offset_position + range_offset + (code - start) as usize * 2usize;
+                match read_u16(data, glyph_position)? {
+                    0 => 0u16,
+                    glyph => glyph.wrapping_add(delta),
+                }
+            };
+            if glyph != 0 {
+                codepoints.insert(code as u32);
+            }
+        }
+    }
+    Some(())
+}
+
+/// Segmented coverage
+fn read_format_12(data: &[u8], subtable: usize, codepoints: &mut HashSet<u32>) -> Option<()> {
+    let group_count: usize = read_u32(data, subtable + 12)? as usize;
+    for group in 0..group_count {
+        let record: usize = subtable + 16usize + group * 12usize;
+        let start: u32 = read_u32(data, record)?;
+        let end: u32 = read_u32(data, record + 4)?;
+        let start_glyph: u32 = read_u32(data, record + 8)?;
+        let end: u32 = end
+            .min(0x10FFFFu32)
+            .min(start.saturating_add(MAX_GROUP_LENGTH - 1u32));
+        for code in start..=end {
+            // Glyph ids past u32 are broken data, not glyphs
+            if start_glyph
+                .checked_add(code - start)
+                .is_some_and(|glyph| glyph != 0)
+            {
+                codepoints.insert(code);
+            }
+        }
+    }
+    Some(())
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAIN_FONT_DATA: &[u8] = include_bytes!("../fonts/Catholicon.ttf");
+    const FALLBACK_FONT_DATA: &[u8] = include_bytes!("../fonts/DejaVuSans.ttf");
+
+    /// Font with nothing but a cmap of one format 12 subtable
+    fn format_12_font(groups: &[(u32, u32, u32)]) -> Vec<u8> {
+        let mut data: Vec<u8> = Vec::new();
+        // Offset table with one table record
+        data.extend_from_slice(&0x00010000u32.to_be_bytes());
+        data.extend_from_slice(&1u16.to_be_bytes());
+        data.extend_from_slice(&[0u8; 6]);
+        data.extend_from_slice(b"cmap");
+        data.extend_from_slice(&0u32.to_be_bytes());
+        data.extend_from_slice(&28u32.to_be_bytes());
+        data.extend_from_slice(&0u32.to_be_bytes());
+        // cmap header, Windows full repertoire subtable
+        data.extend_from_slice(&0u16.to_be_bytes());
+        data.extend_from_slice(&1u16.to_be_bytes());
+        data.extend_from_slice(&3u16.to_be_bytes());
+        data.extend_from_slice(&10u16.to_be_bytes());
+        data.extend_from_slice(&12u32.to_be_bytes());
+        // Format 12 subtable
+        data.extend_from_slice(&12u16.to_be_bytes());
+        data.extend_from_slice(&0u16.to_be_bytes());
+        data.extend_from_slice(&(16u32 + groups.len() as u32 * 12u32).to_be_bytes());
+        data.extend_from_slice(&0u32.to_be_bytes());
+        data.extend_from_slice(&(groups.len() as u32).to_be_bytes());
+        for (start, end, start_glyph) in groups {
+            data.extend_from_slice(&start.to_be_bytes());
+            data.extend_from_slice(&end.to_be_bytes());
+            data.extend_from_slice(&start_glyph.to_be_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn both_fonts_cover_latin() {
+        for data in [MAIN_FONT_DATA, FALLBACK_FONT_DATA] {
+            let codepoints: HashSet<u32> = font_codepoints(data).unwrap();
+            assert!("Aaz09".chars().all(|c| codepoints.contains(&(c as u32))));
+        }
+    }
+
+    #[test]
+    fn only_fallback_font_covers_cyrillic() {
+        let main: HashSet<u32> = font_codepoints(MAIN_FONT_DATA).unwrap();
+        let fallback: HashSet<u32> = font_codepoints(FALLBACK_FONT_DATA).unwrap();
+        for c in "ЖжЯя".chars() {
+            assert!(!main.contains(&(c as u32)));
+            assert!(fallback.contains(&(c as u32)));
+        }
+    }
+
+    #[test]
+    fn broken_data_is_not_a_font() {
+        assert_eq!(font_codepoints(&[]), None);
+        assert_eq!(font_codepoints(&MAIN_FONT_DATA[..64]), None);
+    }
+
+    #[test]
+    fn format_12_glyph_overflow_is_skipped() {
+        let data: Vec<u8> = format_12_font(&[(0x41u32, 0x43u32, u32::MAX - 1u32)]);
+        let codepoints: HashSet<u32> = font_codepoints(&data).unwrap();
+        // 0x41 and 0x42 get the last two glyph ids, 0x43 would be past them
+        assert_eq!(codepoints, HashSet::from([0x41u32, 0x42u32]));
+    }
+
+    #[test]
+    fn format_12_groups_are_capped() {
+        let data: Vec<u8> = format_12_font(&[(0u32, 0x10FFFFu32, 1u32)]);
+        let codepoints: HashSet<u32> = font_codepoints(&data).unwrap();
+        assert_eq!(codepoints.len(), MAX_GROUP_LENGTH as usize);
+    }
+}
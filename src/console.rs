@@ -0,0 +1,413 @@
+use raylib::prelude::*;
+use std::fs;
+
+use crate::global::*;
+use crate::hot_reload::*;
+use crate::run::*;
+use crate::text::*;
+use crate::user_dirs::*;
+
+const TOGGLE_KEY: KeyboardKey = KeyboardKey::KEY_GRAVE;
+const HISTORY_FILE_NAME: &str = "console_history.txt";
+const MAX_HISTORY: usize = 100usize;
+
+/// Developer switches that commands change and the game reads
+pub struct DevCheats {
+    pub game_speed: f32,
+    // Main loop reloads shaders, stage, strings and sprites, then clears it
+    pub reload_requested: bool,
+}
+
+impl DevCheats {
+    pub fn new() -> Self {
+        Self {
+            game_speed: 1f32,
+            reload_requested: false,
+        }
+    }
+}
+
+/// What commands can change
+pub struct ConsoleContext<'a> {
+    pub run: &'a mut RunData,
+    pub game_state: &'a mut GameState,
+    pub cheats: &'a mut DevCheats,
+}
+
+/// Command of the console. Run gets arguments without the name,
+/// and returns text for the console or error
+pub struct ConsoleCommand {
+    pub name: &'static str,
+    pub usage: &'static str,
+    pub run: fn(&mut ConsoleContext, &[&str]) -> Result<String, String>,
+}
+
+/// Drop-down developer console, the key under Escape opens and closes it in developer mode
+pub struct Console {
+    is_enabled: bool,
+    is_open: bool,
+    // How far it is pulled down, 0..1
+    slide: f32,
+    input: String,
+    output: Vec<String>,
+    history: Vec<String>,
+    // Position in history while browsing it with up/down
+    history_index: Option<usize>,
+    commands: Vec<ConsoleCommand>,
+}
+
+impl Console {
+    const FONT_SIZE: f32 = 24f32;
+    const LINE_GAP: f32 = 26f32;
+    const MARGIN: f32 = 8f32;
+    const MAX_OUTPUT: usize = 200usize;
+    // Part of the window height
+    const HEIGHT: f32 = 0.4f32;
+    const LERP_SPEED: f32 = 16f32;
+    const LERP_ACCEPTABLE_ERR: f32 = 0.01f32;
+
+    pub fn new() -> Self {
+        let mut console: Console = Self {
+            is_enabled: is_dev_mode(),
+            is_open: false,
+            slide: 0f32,
+            input: String::new(),
+            output: Vec::new(),
+            history: load_history(),
+            history_index: None,
+            commands: Vec::new(),
+        };
+        console.register_all(builtin_commands());
+        console
+    }
+
+    /// Adds command, a command with the same name is replaced
+    pub fn register(&mut self, command: ConsoleCommand) {
+        self.commands.retain(|known| known.name != command.name);
+        self.commands.push(command);
+    }
+
+    pub fn register_all(&mut self, commands: Vec<ConsoleCommand>) {
+        for command in commands {
+            self.register(command);
+        }
+    }
+
+    /// Game doesn't get keyboard input while console is open
+    pub fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    pub fn update(
+        &mut self,
+        rl: &mut RaylibHandle,
+        delta_time: &f32,
+        context: &mut ConsoleContext,
+    ) {
+        if !self.is_enabled {
+            return;
+        }
+        self.slide = lerp_e(
+            self.slide,
+            if self.is_open { 1f32 } else { 0f32 },
+            delta_time,
+            Self::LERP_SPEED,
+            Self::LERP_ACCEPTABLE_ERR,
+        );
+
+        if rl.is_key_pressed(TOGGLE_KEY) {
+            self.is_open = !self.is_open;
+            // Character of the toggle key must not get into the input
+            while rl.get_char_pressed().is_some() {}
+            return;
+        }
+        if !self.is_open {
+            return;
+        }
+
+        // TEXT INPUT
+        while let Some(character) = rl.get_char_pressed() {
+            if !character.is_control() {
+                self.input.push(character);
+            }
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE)
+            || rl.is_key_pressed_repeat(KeyboardKey::KEY_BACKSPACE)
+        {
+            self.input.pop();
+        }
+
+        // HISTORY
+        if rl.is_key_pressed(KeyboardKey::KEY_UP) && !self.history.is_empty() {
+            let index: usize = match self.history_index {
+                Some(index) => index.saturating_sub(1usize),
+                None => self.history.len() - 1usize,
+            };
+            self.history_index = Some(index);
+            self.input = self.history[index].clone();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
+            if let Some(index) = self.history_index {
+                if index + 1usize < self.history.len() {
+                    self.history_index = Some(index + 1usize);
+                    self.input = self.history[index + 1usize].clone();
+                } else {
+                    self.history_index = None;
+                    self.input.clear();
+                }
+            }
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            let line: String = self.input.trim().to_string();
+            self.input.clear();
+            self.history_index = None;
+            if !line.is_empty() {
+                self.execute(&line, context);
+            }
+        }
+    }
+
+    /// Runs a command line and writes its result to the output
+    pub fn execute(&mut self, line: &str, context: &mut ConsoleContext) {
+        self.print(&format!("> {}", line));
+        if self.history.last().map(|last| last.as_str()) != Some(line) {
+            self.history.push(line.to_string());
+            if self.history.len() > MAX_HISTORY {
+                self.history.remove(0);
+            }
+            save_history(&self.history);
+        }
+
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (name, args) = match words.split_first() {
+            Some((name, args)) => (*name, args),
+            None => return,
+        };
+        // Commands that work on the console itself
+        match name {
+            "help" => {
+                let usages: Vec<String> = self
+                    .commands
+                    .iter()
+                    .map(|command| command.usage.to_string())
+                    .collect();
+                self.print("help, clear");
+                for usage in usages {
+                    self.print(&usage);
+                }
+                return;
+            }
+            "clear" => {
+                self.output.clear();
+                return;
+            }
+            _ => {}
+        }
+
+        let result: Result<String, String> =
+            match self.commands.iter().find(|command| command.name == name) {
+                Some(command) => (command.run)(context, args)
+                    .map_err(|err| format!("{}\nusage: {}", err, command.usage)),
+                None => Err(format!("Unknown command '{}', try help", name)),
+            };
+        match result {
+            Ok(text) if text.is_empty() => {}
+            Ok(text) => self.print(&text),
+            Err(err) => self.print(&err),
+        }
+    }
+
+    fn print(&mut self, text: &str) {
+        for line in text.lines() {
+            self.output.push(line.to_string());
+        }
+        if self.output.len() > Self::MAX_OUTPUT {
+            self.output.drain(0..self.output.len() - Self::MAX_OUTPUT);
+        }
+    }
+
+    /// Drawn over the whole window from its top
+    pub fn draw(&self, d: &mut RaylibDrawHandle, font: &Font, cheats: &DevCheats, run: &RunData) {
+        if self.slide <= 0f32 {
+            return;
+        }
+
+        let width: f32 = d.get_screen_width() as f32;
+        let height: f32 = d.get_screen_height() as f32 * Self::HEIGHT;
+        let top: f32 = height * (self.slide - 1f32);
+        d.draw_rectangle_v(
+            Vector2::new(0f32, top),
+            Vector2::new(width, height),
+            Color::BLACK.alpha(0.85f32),
+        );
+
+        // Prompt at the bottom, output above it from the newest line
+        let style: TextStyle = TextStyle::new(Self::FONT_SIZE, Color::WHITE);
+        let prompt_y: f32 = top + height - Self::LINE_GAP - Self::MARGIN;
+        draw_text(
+            d,
+            font,
+            &format!("> {}_", self.input),
+            Vector2::new(Self::MARGIN, prompt_y),
+            &style,
+        );
+        let mut y: f32 = prompt_y - Self::LINE_GAP;
+        for line in self.output.iter().rev() {
+            if y < top {
+                break;
+            }
+            draw_text(
+                d,
+                font,
+                line,
+                Vector2::new(Self::MARGIN, y),
+                &TextStyle::new(Self::FONT_SIZE, Color::LIGHTGRAY),
+            );
+            y -= Self::LINE_GAP;
+        }
+
+        // Active cheats in the corner
+        let status: String = format!(
+            "speed x{}{}",
+            cheats.game_speed,
+            if run.is_invincible {
+                ", invincible"
+            } else {
+                ""
+            }
+        );
+        draw_text(
+            d,
+            font,
+            &status,
+            Vector2::new(width - Self::MARGIN, prompt_y),
+            &TextStyle::new(Self::FONT_SIZE, Color::GOLD).align(Align::Right),
+        );
+    }
+}
+
+/// Commands of run values, cheats and assets
+pub fn builtin_commands() -> Vec<ConsoleCommand> {
+    vec![
+        ConsoleCommand {
+            name: "lives",
+            usage: "lives <count>",
+            run: |context, args| {
+                context.run.lives = parse_arg(args, 0usize)?;
+                context.run.is_cheated = true;
+                Ok(format!("lives = {}", context.run.lives))
+            },
+        },
+        ConsoleCommand {
+            name: "bombs",
+            usage: "bombs <count>",
+            run: |context, args| {
+                context.run.bombs = parse_arg(args, 0usize)?;
+                context.run.is_cheated = true;
+                Ok(format!("bombs = {}", context.run.bombs))
+            },
+        },
+        ConsoleCommand {
+            name: "god",
+            usage: "god",
+            run: |context, _| {
+                context.run.is_invincible = !context.run.is_invincible;
+                context.run.is_cheated = true;
+                Ok(format!("invincible = {}", context.run.is_invincible))
+            },
+        },
+        ConsoleCommand {
+            name: "miss",
+            usage: "miss",
+            run: |context, _| {
+                context.run.miss();
+                context.run.is_cheated = true;
+                Ok(format!("lives = {}", context.run.lives))
+            },
+        },
+        ConsoleCommand {
+            name: "power",
+            usage: "power <0..4>",
+            run: |context, args| {
+                let power: f32 = parse_finite_arg(args, 0usize)?;
+                context.run.power = power.clamp(0f32, MAX_POWER);
+                context.run.is_cheated = true;
+                Ok(format!("power = {}", context.run.power))
+            },
+        },
+        ConsoleCommand {
+            name: "speed",
+            usage: "speed <0.1..4>",
+            run: |context, args| {
+                // Gameplay steps stay the same, only more or fewer of them run per second
+                let speed: f32 = parse_finite_arg(args, 0usize)?;
+                context.cheats.game_speed = speed.clamp(0.1f32, 4f32);
+                Ok(format!("game speed = {}", context.cheats.game_speed))
+            },
+        },
+        ConsoleCommand {
+            name: "seed",
+            usage: "seed <number>",
+            run: |context, args| {
+                let seed: u64 = parse_arg(args, 0usize)?;
+                // Run starts the stage over like its replay will, values put in mid-run are gone
+                let run: &mut RunData = context.run;
+                *run = RunData::with_seed(run.difficulty, run.stage, seed);
+                *context.game_state = GameState::Playing;
+                Ok(format!("seed = {}, stage {} starts over", seed, run.stage))
+            },
+        },
+        ConsoleCommand {
+            name: "rng",
+            usage: "rng",
+            run: |context, _| {
+                // Only shown, a replay can't follow a state put in mid-run, "seed" restarts it
+                Ok(context.run.rng.save_state())
+            },
+        },
+        ConsoleCommand {
+            name: "reload",
+            usage: "reload",
+            run: |context, _| {
+                context.cheats.reload_requested = true;
+                Ok("Reloading shaders, stage, strings and sprites".to_string())
+            },
+        },
+    ]
+}
+
+/// Parses argument by its index
+pub fn parse_arg<T: std::str::FromStr>(args: &[&str], index: usize) -> Result<T, String> {
+    let arg: &str = args
+        .get(index)
+        .ok_or_else(|| format!("Argument {} is missing", index + 1usize))?;
+    arg.parse()
+        .map_err(|_| format!("Can't parse argument '{}'", arg))
+}
+
+/// Parses number argument by its index, NaN and infinity are errors since clamping keeps them
+pub fn parse_finite_arg(args: &[&str], index: usize) -> Result<f32, String> {
+    let value: f32 = parse_arg(args, index)?;
+    if !value.is_finite() {
+        return Err(format!(
+            "Argument {} must be a finite number",
+            index + 1usize
+        ));
+    }
+    Ok(value)
+}
+
+fn load_history() -> Vec<String> {
+    fs::read_to_string(data_path(HISTORY_FILE_NAME))
+        .map(|data| data.lines().map(|line| line.to_string()).collect())
+        .unwrap_or_default()
+}
+
+fn save_history(history: &[String]) {
+    let data: String = history.iter().map(|line| format!("{}\n", line)).collect();
+    let path: String = data_path(HISTORY_FILE_NAME);
+    if let Err(err) = fs::write(&path, data.as_bytes()) {
+        log_warn!("{}: {}", path, err);
+    }
+}
@@ -0,0 +1,124 @@
+use std::backtrace::Backtrace;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::panic::{self, PanicHookInfo};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::global::*;
+use crate::log::*;
+use crate::run::*;
+use crate::user_dirs::*;
+
+const CRASH_DIR_NAME: &str = "crashes";
+
+/// What the panic hook can't see by itself, main loop keeps it current
+struct CrashContext {
+    game_state: String,
+    options: String,
+    // Report of the last panic, the replay line is added to it
+    report_path: Option<String>,
+}
+
+static CRASH_CONTEXT: Mutex<CrashContext> = Mutex::new(CrashContext {
+    game_state: String::new(),
+    options: String::new(),
+    report_path: None,
+});
+
+/// Panic hook that writes a crash report into the data directory after the usual panic message
+pub fn install_crash_handler() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        let report: String = crash_report(info, &Backtrace::force_capture());
+        // Log macros are not used here, the panic may come from inside the logger
+        match write_report(&report) {
+            Ok(path) => {
+                eprintln!("Crash report written to {}", path);
+                if let Ok(mut context) = CRASH_CONTEXT.try_lock() {
+                    context.report_path = Some(path);
+                }
+            }
+            Err(err) => eprintln!("Crash report failed: {}", err),
+        }
+    }));
+}
+
+/// Game state and options that go into the report
+pub fn set_crash_context(game_state: GameState, gd: &GameData) {
+    let mut context = CRASH_CONTEXT.lock().unwrap_or_else(|err| err.into_inner());
+    context.game_state = format!("{:?}", game_state);
+    context.options = gd.summary();
+}
+
+/// Writes replay of the run that crashed, and names it in the crash report
+pub fn save_crash_replay(run: &RunData) {
+    if run.is_cheated {
+        log_info!("Replay of the crashed run is not saved, console cheats changed it");
+        return;
+    }
+    let replay_path: String = match run.replay.save() {
+        Some(replay_path) => replay_path,
+        None => {
+            log_error!("Replay of the crashed run could not be saved");
+            return;
+        }
+    };
+    log_info!("Replay of the crashed run saved to {}", replay_path);
+
+    let context = CRASH_CONTEXT.lock().unwrap_or_else(|err| err.into_inner());
+    if let Some(report_path) = &context.report_path {
+        let result: std::io::Result<()> = OpenOptions::new()
+            .append(true)
+            .open(report_path)
+            .and_then(|mut file| writeln!(file, "\nReplay: {}", replay_path));
+        if let Err(err) = result {
+            log_error!("{}: {}", report_path, err);
+        }
+    }
+}
+
+fn crash_report(info: &PanicHookInfo, backtrace: &Backtrace) -> String {
+    let message: &str = match info.payload().downcast_ref::<&str>() {
+        Some(message) => message,
+        None => match info.payload().downcast_ref::<String>() {
+            Some(message) => message,
+            None => "Unknown panic",
+        },
+    };
+    let location: String = info
+        .location()
+        .map(|location| format!("{}:{}", location.file(), location.line()))
+        .unwrap_or_else(|| "unknown".to_string());
+    let (game_state, options) = match CRASH_CONTEXT.try_lock() {
+        Ok(context) if !context.game_state.is_empty() => {
+            (context.game_state.clone(), context.options.clone())
+        }
+        _ => ("Starting".to_string(), "Not loaded".to_string()),
+    };
+
+    format!(
+        "Noster: Mare Frigoris {} crash report\n\nPanic: {}\nLocation: {}\nGame state: {}\n\nOptions:\n{}\n\nBacktrace:\n{}\n\nLast log lines:\n{}\n",
+        env!("CARGO_PKG_VERSION"),
+        message,
+        location,
+        game_state,
+        options,
+        backtrace,
+        recent_log_lines().join("\n")
+    )
+}
+
+/// Returns path of the written report
+fn write_report(report: &str) -> Result<String, String> {
+    let dir: String = data_path(CRASH_DIR_NAME);
+    fs::create_dir_all(&dir).map_err(|err| format!("{}: {}", dir, err))?;
+    let timestamp: u64 = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or(0u64);
+    let path: String = format!("{}/crash_{}.txt", dir, timestamp);
+    fs::write(&path, report.as_bytes()).map_err(|err| format!("{}: {}", path, err))?;
+    Ok(path)
+}
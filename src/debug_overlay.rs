@@ -0,0 +1,186 @@
+use raylib::prelude::*;
+use std::time::Instant;
+
+use crate::global::*;
+use crate::text::*;
+
+const TOGGLE_KEY: KeyboardKey = KeyboardKey::KEY_F3;
+
+/// What the game shows to the overlay this frame
+pub struct DebugInfo {
+    pub game_state: String,
+    pub menu_state: String,
+    pub bullets: usize,
+    pub enemies: usize,
+    pub items: usize,
+    // Center and radius in viewport space
+    pub hitboxes: Vec<(Vector2, f32)>,
+}
+
+/// Developer overlay, only compiled with the "debug_overlay" feature.
+/// F3 shows frame time graph, update/draw split, entity counts, states and hitboxes
+pub struct DebugOverlay {
+    is_shown: bool,
+    // Last frame times in seconds, oldest first
+    frame_times: Vec<f32>,
+    update_start: Option<Instant>,
+    draw_start: Option<Instant>,
+    update_time: f32,
+    draw_time: f32,
+}
+
+impl DebugOverlay {
+    const GRAPH_FRAMES: usize = 120usize;
+    const GRAPH_HEIGHT: f32 = 80f32;
+    // Frame time at the top of the graph
+    const GRAPH_MAX_TIME: f32 = 1f32 / 20f32;
+    const FONT_SIZE: f32 = 22f32;
+    const LINE_GAP: f32 = 24f32;
+    const MARGIN: f32 = 8f32;
+    const PANEL_WIDTH: f32 = 260f32;
+
+    pub fn new() -> Self {
+        Self {
+            is_shown: false,
+            frame_times: Vec::with_capacity(Self::GRAPH_FRAMES),
+            update_start: None,
+            draw_start: None,
+            update_time: 0f32,
+            draw_time: 0f32,
+        }
+    }
+
+    /// Toggle key and frame time of the last frame, update timing starts here
+    pub fn update(&mut self, rl: &RaylibHandle, delta_time: &f32) {
+        self.update_start = Some(Instant::now());
+        if rl.is_key_pressed(TOGGLE_KEY) {
+            self.is_shown = !self.is_shown;
+        }
+        if self.frame_times.len() >= Self::GRAPH_FRAMES {
+            self.frame_times.remove(0);
+        }
+        self.frame_times.push(*delta_time);
+    }
+
+    pub fn is_shown(&self) -> bool {
+        self.is_shown
+    }
+
+    pub fn end_update(&mut self) {
+        if let Some(start) = self.update_start.take() {
+            self.update_time = start.elapsed().as_secs_f32();
+        }
+    }
+
+    pub fn begin_draw(&mut self) {
+        self.draw_start = Some(Instant::now());
+    }
+
+    pub fn end_draw(&mut self) {
+        if let Some(start) = self.draw_start.take() {
+            self.draw_time = start.elapsed().as_secs_f32();
+        }
+    }
+
+    /// Drawn over the window, hitboxes are mapped from viewport like the viewport itself
+    pub fn draw(&self, d: &mut RaylibDrawHandle, gd: &GameData, font: &Font, info: &DebugInfo) {
+        if !self.is_shown {
+            return;
+        }
+
+        // HITBOXES
+        let target: Rectangle = target_rect(
+            gd.scaling_mode(),
+            d.get_screen_width(),
+            d.get_screen_height(),
+        );
+        let scale: Vector2 = Vector2::new(
+            target.width / SCREEN_WIDTH as f32,
+            target.height / SCREEN_HEIGHT as f32,
+        );
+        for (center, radius) in info.hitboxes.iter() {
+            let position: Vector2 =
+                Vector2::new(target.x + center.x * scale.x, target.y + center.y * scale.y);
+            d.draw_circle_lines(
+                position.x as i32,
+                position.y as i32,
+                (radius * scale.x.min(scale.y)).max(1f32),
+                Color::LIME,
+            );
+        }
+
+        // PANEL
+        let lines: [String; 6] = [
+            format!(
+                "Frame {:.2} ms",
+                self.frame_times.last().copied().unwrap_or(0f32) * 1000f32
+            ),
+            format!(
+                "Update {:.2} ms  Draw {:.2} ms",
+                self.update_time * 1000f32,
+                self.draw_time * 1000f32
+            ),
+            format!(
+                "Bullets {}  Enemies {}  Items {}",
+                info.bullets, info.enemies, info.items
+            ),
+            format!("Game {}", info.game_state),
+            format!("Menu {}", info.menu_state),
+            format!("Hitboxes {}", info.hitboxes.len()),
+        ];
+        let panel_position: Vector2 = Vector2::new(
+            d.get_screen_width() as f32 - Self::PANEL_WIDTH - Self::MARGIN,
+            Self::MARGIN,
+        );
+        let panel_height: f32 =
+            Self::GRAPH_HEIGHT + Self::LINE_GAP * lines.len() as f32 + Self::MARGIN * 3f32;
+        d.draw_rectangle_v(
+            panel_position,
+            Vector2::new(Self::PANEL_WIDTH, panel_height),
+            Color::BLACK.alpha(0.8f32),
+        );
+
+        // Frame time graph, one bar per frame, lines at 60 and 30 fps
+        let graph_bottom: f32 = panel_position.y + Self::MARGIN + Self::GRAPH_HEIGHT;
+        let bar_width: f32 = (Self::PANEL_WIDTH - Self::MARGIN * 2f32) / Self::GRAPH_FRAMES as f32;
+        for (i, frame_time) in self.frame_times.iter().enumerate() {
+            let height: f32 = (frame_time / Self::GRAPH_MAX_TIME).min(1f32) * Self::GRAPH_HEIGHT;
+            let color: Color = if *frame_time > 1f32 / 30f32 {
+                Color::RED
+            } else if *frame_time > 1f32 / 60f32 {
+                Color::YELLOW
+            } else {
+                Color::LIME
+            };
+            d.draw_rectangle_v(
+                Vector2::new(
+                    panel_position.x + Self::MARGIN + bar_width * i as f32,
+                    graph_bottom - height,
+                ),
+                Vector2::new(bar_width, height),
+                color,
+            );
+        }
+        for fps in [60f32, 30f32] {
+            let y: f32 = graph_bottom - (1f32 / fps / Self::GRAPH_MAX_TIME) * Self::GRAPH_HEIGHT;
+            d.draw_line_v(
+                Vector2::new(panel_position.x + Self::MARGIN, y),
+                Vector2::new(panel_position.x + Self::PANEL_WIDTH - Self::MARGIN, y),
+                Color::WHITE.alpha(0.5f32),
+            );
+        }
+
+        for (i, line) in lines.iter().enumerate() {
+            draw_text(
+                d,
+                font,
+                line,
+                Vector2::new(
+                    panel_position.x + Self::MARGIN,
+                    graph_bottom + Self::MARGIN + Self::LINE_GAP * i as f32,
+                ),
+                &TextStyle::new(Self::FONT_SIZE, Color::WHITE),
+            );
+        }
+    }
+}
@@ -0,0 +1,511 @@
+use raylib::prelude::*;
+
+use crate::archive::*;
+use crate::assets::*;
+use crate::global::*;
+use crate::locale::*;
+use crate::run::*;
+use crate::save_data::*;
+use crate::text::*;
+
+const ENDINGS_DIR_PATH: &str = "assets/endings";
+const CREDITS_FILE_PATH: &str = "assets/credits.txt";
+
+pub struct EndScreen<'aud> {
+    is_entered: bool,
+    end_state: EndState,
+    ending_id: String,
+    ending: Ending,
+    textures: Vec<Option<Texture2D>>,
+    credits: Vec<CreditLine>,
+    bgm: Option<MusicStream<'aud>>,
+    current_slide: usize,
+    timer: f32,
+    scroll: f32,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum EndState {
+    Slides,    // Story text and pictures
+    StaffRoll, // Credits scrolling up
+    Finished,  // The End
+}
+
+struct Slide {
+    duration: f32,
+    text: String,
+    image_path: Option<String>,
+}
+
+/// Contents of an ending file
+struct Ending {
+    bgm_path: Option<String>,
+    staff_bgm_path: Option<String>,
+    slides: Vec<Slide>,
+}
+
+enum CreditLine {
+    Heading(String),
+    Name(String),
+    Gap,
+}
+
+impl<'aud> EndScreen<'aud> {
+    const FADE_TIME: f32 = 0.6f32;
+    const FAST_FORWARD_SPEED: f32 = 4f32;
+    const SCROLL_SPEED: f32 = 60f32;
+    const THE_END_TIME: f32 = 5f32;
+    const HEADING_SIZE: f32 = 64f32;
+    const NAME_SIZE: f32 = 44f32;
+    const LINE_GAP: f32 = 16f32;
+
+    pub fn new() -> Self {
+        Self {
+            is_entered: false,
+            end_state: EndState::Slides,
+            ending_id: String::new(),
+            ending: Ending {
+                bgm_path: None,
+                staff_bgm_path: None,
+                slides: Vec::new(),
+            },
+            textures: Vec::new(),
+            credits: Vec::new(),
+            bgm: None,
+            current_slide: 0usize,
+            timer: 0f32,
+            scroll: 0f32,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn update(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        audio: &'aud RaylibAudio,
+        gd: &GameData,
+        delta_time: &f32,
+        run: &RunData,
+        save_data: &mut SaveData,
+        game_state: &mut GameState,
+    ) {
+        if !self.is_entered {
+            self.enter(rl, thread, audio, gd, run);
+        }
+
+        if let Some(bgm) = &self.bgm {
+            bgm.update_stream();
+        }
+
+        // Holding attack fast-forwards everything
+        let step: f32 = if rl.is_key_down(gd.key("attack")) {
+            delta_time * Self::FAST_FORWARD_SPEED
+        } else {
+            *delta_time
+        };
+
+        match self.end_state {
+            EndState::Slides => {
+                self.timer += step;
+
+                let is_skipped: bool =
+                    rl.is_key_pressed(ACCEPT) && save_data.has_seen_ending(&self.ending_id);
+                let slide_duration: f32 = self
+                    .ending
+                    .slides
+                    .get(self.current_slide)
+                    .map(|slide| slide.duration)
+                    .unwrap_or(0f32);
+
+                if self.timer >= slide_duration {
+                    self.timer = 0f32;
+                    self.current_slide += 1;
+                }
+                if self.current_slide >= self.ending.slides.len() || is_skipped {
+                    self.start_staff_roll(audio, gd, save_data);
+                }
+            }
+            EndState::StaffRoll => {
+                self.scroll += Self::SCROLL_SPEED * step;
+                if self.scroll > self.credits_height() + SCREEN_HEIGHT as f32 {
+                    self.timer = 0f32;
+                    self.end_state = EndState::Finished;
+                }
+            }
+            EndState::Finished => {
+                self.timer += step;
+                if self.timer >= Self::THE_END_TIME || rl.is_key_pressed(ACCEPT) {
+                    self.leave(game_state);
+                }
+            }
+        }
+    }
+
+    pub fn draw(
+        &self,
+        thread: &RaylibThread,
+        d: &mut RaylibDrawHandle,
+        gd: &GameData,
+        font: &Font,
+        locale: &Locale,
+        render_target: &mut RenderTexture2D,
+    ) {
+        // DRAW IN VIEWPORT
+        {
+            let mut d = d.begin_texture_mode(thread, render_target);
+            d.clear_background(Color::BLACK);
+
+            match self.end_state {
+                EndState::Slides => {
+                    const TEXT_SIZE: f32 = 44f32;
+                    const TEXT_POSITION: f32 = SCREEN_HEIGHT as f32 - 240f32;
+                    const IMAGE_MAX_WIDTH: f32 = SCREEN_WIDTH as f32 - 120f32;
+                    const IMAGE_MAX_HEIGHT: f32 = 560f32;
+
+                    if let Some(slide) = self.ending.slides.get(self.current_slide) {
+                        // Fade in on start and fade out on the end of the slide
+                        let alpha: f32 = (self.timer / Self::FADE_TIME)
+                            .min((slide.duration - self.timer) / Self::FADE_TIME)
+                            .clamp(0f32, 1f32);
+
+                        if let Some(Some(texture)) = self.textures.get(self.current_slide) {
+                            let scale: f32 = (IMAGE_MAX_WIDTH / texture.width as f32)
+                                .min(IMAGE_MAX_HEIGHT / texture.height as f32);
+                            d.draw_texture_ex(
+                                texture,
+                                Vector2::new(
+                                    (SCREEN_WIDTH as f32 - texture.width as f32 * scale) / 2f32,
+                                    80f32,
+                                ),
+                                0f32,
+                                scale,
+                                Color::WHITE.alpha(alpha),
+                            );
+                        }
+
+                        draw_text(
+                            &mut d,
+                            font,
+                            &slide.text,
+                            Vector2::new(SCREEN_WIDTH as f32 / 2f32, TEXT_POSITION),
+                            &TextStyle::new(TEXT_SIZE, Color::WHITE.alpha(alpha))
+                                .align(Align::Center)
+                                .wrap(IMAGE_MAX_WIDTH),
+                        );
+                    }
+                }
+                EndState::StaffRoll => {
+                    let mut y: f32 = SCREEN_HEIGHT as f32 - self.scroll;
+                    for line in &self.credits {
+                        // Only lines on screen are drawn
+                        if y > -Self::HEADING_SIZE && y < SCREEN_HEIGHT as f32 {
+                            let (text, size, color) = match line {
+                                CreditLine::Heading(text) => {
+                                    (text.as_str(), Self::HEADING_SIZE, Color::DARKMAGENTA)
+                                }
+                                CreditLine::Name(text) => {
+                                    (text.as_str(), Self::NAME_SIZE, Color::WHITE)
+                                }
+                                CreditLine::Gap => ("", Self::NAME_SIZE, Color::WHITE),
+                            };
+                            draw_text(
+                                &mut d,
+                                font,
+                                text,
+                                Vector2::new(SCREEN_WIDTH as f32 / 2f32, y),
+                                &TextStyle::new(size, color).align(Align::Center),
+                            );
+                        }
+                        y += Self::credit_line_height(line);
+                    }
+                }
+                EndState::Finished => {
+                    const FONT_SIZE: f32 = 84f32;
+                    let alpha: f32 = (self.timer / Self::FADE_TIME).clamp(0f32, 1f32);
+                    draw_text(
+                        &mut d,
+                        font,
+                        locale.tr("end.the_end"),
+                        Vector2::new(
+                            SCREEN_WIDTH as f32 / 2f32,
+                            SCREEN_HEIGHT as f32 / 2f32 - FONT_SIZE / 2f32,
+                        ),
+                        &TextStyle::new(FONT_SIZE, Color::WHITE.alpha(alpha))
+                            .align(Align::Center)
+                            .outline(2f32, Color::DARKMAGENTA),
+                    );
+                }
+            }
+        }
+        draw_on_target(d, render_target, gd.scaling_mode());
+    }
+
+    /// Picks ending for a finished run and loads its slides, pictures, credits and music
+    fn enter(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        audio: &'aud RaylibAudio,
+        gd: &GameData,
+        run: &RunData,
+    ) {
+        self.is_entered = true;
+        self.end_state = EndState::Slides;
+        self.current_slide = 0usize;
+        self.timer = 0f32;
+        self.scroll = 0f32;
+
+        // Difficulty specific ending wins over the general one
+        let kind: &str = ending_kind(run);
+        let difficulty_id: String = format!("{}_{}", run.difficulty.as_str().to_lowercase(), kind);
+        let mut ending_data: Option<String> = read_file_string(&ending_path(&difficulty_id));
+        if ending_data.is_some() {
+            self.ending_id = difficulty_id;
+        } else {
+            self.ending_id = kind.to_string();
+            ending_data = read_file_string(&ending_path(kind));
+        }
+        self.ending = match ending_data {
+            Some(data) => parse_ending(&data),
+            None => {
+                log_warn!("Ending '{}' is missing, going straight to staff roll", kind);
+                Ending {
+                    bgm_path: None,
+                    staff_bgm_path: None,
+                    slides: Vec::new(),
+                }
+            }
+        };
+
+        self.textures = self
+            .ending
+            .slides
+            .iter()
+            .map(|slide| {
+                slide.image_path.as_ref().and_then(|path| {
+                    let texture: Option<Texture2D> = load_texture_file(rl, thread, path);
+                    if texture.is_none() {
+                        log_warn!("Ending image {} failed to load", path);
+                    }
+                    texture
+                })
+            })
+            .collect();
+
+        self.credits = match read_file_string(CREDITS_FILE_PATH) {
+            Some(data) => parse_credits(&data),
+            None => {
+                log_warn!("{} is missing, staff roll is empty", CREDITS_FILE_PATH);
+                Vec::new()
+            }
+        };
+
+        let bgm_path: Option<String> = self.ending.bgm_path.clone();
+        self.play_bgm(audio, gd, bgm_path);
+    }
+
+    fn start_staff_roll(
+        &mut self,
+        audio: &'aud RaylibAudio,
+        gd: &GameData,
+        save_data: &mut SaveData,
+    ) {
+        self.end_state = EndState::StaffRoll;
+        self.timer = 0f32;
+        self.scroll = 0f32;
+        save_data.mark_ending_seen(&self.ending_id);
+
+        let staff_bgm_path: Option<String> = self.ending.staff_bgm_path.clone();
+        if staff_bgm_path.is_some() {
+            self.play_bgm(audio, gd, staff_bgm_path);
+        }
+    }
+
+    /// Stops current music and starts the new one, missing music is not fatal
+    fn play_bgm(&mut self, audio: &'aud RaylibAudio, gd: &GameData, path: Option<String>) {
+        if let Some(bgm) = &self.bgm {
+            bgm.stop_stream();
+        }
+        self.bgm = path.and_then(|path| match load_music_file(audio, &path) {
+            Some(bgm) => {
+                bgm.set_volume(gd.get_bgm_volume());
+                bgm.play_stream();
+                Some(bgm)
+            }
+            None => {
+                log_warn!("Ending music {} failed to load", path);
+                None
+            }
+        });
+    }
+
+    /// Frees ending resources and goes back to the title
+    fn leave(&mut self, game_state: &mut GameState) {
+        if let Some(bgm) = &self.bgm {
+            bgm.stop_stream();
+        }
+        self.bgm = None;
+        self.textures.clear();
+        self.is_entered = false;
+        *game_state = GameState::MainMenu;
+    }
+
+    fn credit_line_height(line: &CreditLine) -> f32 {
+        match line {
+            CreditLine::Heading(_) => Self::HEADING_SIZE + Self::LINE_GAP,
+            CreditLine::Name(_) | CreditLine::Gap => Self::NAME_SIZE + Self::LINE_GAP,
+        }
+    }
+
+    fn credits_height(&self) -> f32 {
+        self.credits.iter().map(Self::credit_line_height).sum()
+    }
+}
+
+/// Extra has own ending, continues or Easy lead to the bad one
+fn ending_kind(run: &RunData) -> &'static str {
+    if run.difficulty == Difficulty::Extra {
+        "extra"
+    } else if run.continues_used > 0 || run.difficulty == Difficulty::Easy {
+        "bad"
+    } else {
+        "good"
+    }
+}
+
+fn ending_path(ending_id: &str) -> String {
+    format!("{}/{}.txt", ENDINGS_DIR_PATH, ending_id)
+}
+
+/// Ending file format, one entry per line:
+/// bgm <path> | staff_bgm <path> | slide <seconds> <text> | image <seconds> <path> <text>
+fn parse_ending(data: &str) -> Ending {
+    let mut ending: Ending = Ending {
+        bgm_path: None,
+        staff_bgm_path: None,
+        slides: Vec::new(),
+    };
+
+    for line in data.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+        match command {
+            "bgm" => ending.bgm_path = Some(rest.trim().to_string()),
+            "staff_bgm" => ending.staff_bgm_path = Some(rest.trim().to_string()),
+            "slide" | "image" => {
+                let (duration, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+                let duration: f32 = match duration.parse() {
+                    Ok(duration) => duration,
+                    Err(_) => {
+                        log_warn!("Bad slide duration in ending: '{}'", line);
+                        continue;
+                    }
+                };
+                let (image_path, text) = if command == "image" {
+                    let (path, text) = rest.split_once(' ').unwrap_or((rest, ""));
+                    (Some(path.to_string()), text)
+                } else {
+                    (None, rest)
+                };
+                ending.slides.push(Slide {
+                    duration,
+                    text: text.to_string(),
+                    image_path,
+                });
+            }
+            _ => log_warn!("Unknown ending command: '{}'", line),
+        }
+    }
+    ending
+}
+
+/// Credits format: "= text" is a heading, empty line is a gap, anything else is a name
+fn parse_credits(data: &str) -> Vec<CreditLine> {
+    data.lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .map(|line| {
+            if line.is_empty() {
+                CreditLine::Gap
+            } else if let Some(heading) = line.strip_prefix('=') {
+                CreditLine::Heading(heading.trim().to_string())
+            } else {
+                CreditLine::Name(line.to_string())
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ending_has_music_and_slides_in_order() {
+        let ending: Ending = parse_ending(
+            "# comment\n\
+             bgm bgm/ending.ogg\n\
+             staff_bgm  bgm/staff.ogg \n\
+             \n\
+             slide 4 The moon is quiet again.\n\
+             image 2.5 assets/endings/sea.png Waves of the frozen sea\n\
+             image 3 assets/endings/empty.png\n",
+        );
+        assert_eq!(ending.bgm_path.as_deref(), Some("bgm/ending.ogg"));
+        assert_eq!(ending.staff_bgm_path.as_deref(), Some("bgm/staff.ogg"));
+        let slides: Vec<(f32, &str, Option<&str>)> = ending
+            .slides
+            .iter()
+            .map(|slide| {
+                (
+                    slide.duration,
+                    slide.text.as_str(),
+                    slide.image_path.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            slides,
+            vec![
+                (4f32, "The moon is quiet again.", None),
+                (
+                    2.5f32,
+                    "Waves of the frozen sea",
+                    Some("assets/endings/sea.png")
+                ),
+                (3f32, "", Some("assets/endings/empty.png")),
+            ]
+        );
+    }
+
+    #[test]
+    fn bad_ending_lines_are_skipped() {
+        let ending: Ending = parse_ending("slide soon Text\nfade 2\nslide 1 Kept\n");
+        assert!(ending.bgm_path.is_none() && ending.staff_bgm_path.is_none());
+        assert_eq!(ending.slides.len(), 1usize);
+        assert_eq!(ending.slides[0].text, "Kept");
+    }
+
+    #[test]
+    fn credits_have_headings_names_and_gaps() {
+        let credits: Vec<CreditLine> = parse_credits("# comment\n= Music\n  bk  \n\n=Font\n");
+        assert_eq!(credits.len(), 4usize);
+        assert!(matches!(&credits[0], CreditLine::Heading(text) if text == "Music"));
+        assert!(matches!(&credits[1], CreditLine::Name(text) if text == "bk"));
+        assert!(matches!(&credits[2], CreditLine::Gap));
+        assert!(matches!(&credits[3], CreditLine::Heading(text) if text == "Font"));
+    }
+
+    #[test]
+    fn ending_follows_difficulty_and_continues() {
+        let mut run: RunData = RunData::new(Difficulty::Hard);
+        assert_eq!(ending_kind(&run), "good");
+        run.continues_used = 1u8;
+        assert_eq!(ending_kind(&run), "bad");
+        assert_eq!(ending_kind(&RunData::new(Difficulty::Easy)), "bad");
+        assert_eq!(ending_kind(&RunData::new(Difficulty::Extra)), "extra");
+    }
+}
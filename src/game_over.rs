@@ -0,0 +1,339 @@
+use raylib::prelude::*;
+
+use crate::global::*;
+use crate::locale::*;
+use crate::menu::*;
+use crate::run::*;
+use crate::score::*;
+use crate::text::*;
+
+pub struct GameOverScreen {
+    is_entered: bool,
+    screen_state: GameOverState,
+    menu_page: MenuPage<GameOverAction>,
+    text_pos_x: f32,
+    // Name entry
+    name: String,
+    name_char_index: usize,
+    // Replay
+    replay_saved: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum GameOverState {
+    NameEntry, // Score qualified for the table
+    Menu,      // Continue, Retry, Save Replay, Return to Title
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum GameOverAction {
+    Continue,
+    Retry,
+    SaveReplay,
+    ReturnToTitle,
+}
+
+impl GameOverScreen {
+    const TARGET_TEXT_POS: f32 = 96f32;
+    const INITIAL_TEXT_POS: f32 = -380f32;
+    const LERP_SPEED: f32 = 24f32;
+    const LERP_ACCEPTABLE_ERR: f32 = 0.8f32;
+    // Last "character" of the set finishes the entry
+    const NAME_CHARS: &'static str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789.-!?";
+
+    pub fn new() -> Self {
+        Self {
+            is_entered: false,
+            screen_state: GameOverState::Menu,
+            menu_page: Self::menu_page(),
+            text_pos_x: Self::INITIAL_TEXT_POS,
+            name: String::new(),
+            name_char_index: 0usize,
+            replay_saved: false,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn update(
+        &mut self,
+        rl: &mut RaylibHandle,
+        gd: &mut GameData,
+        locale: &Locale,
+        delta_time: &f32,
+        run: &mut RunData,
+        scores: &mut HighScores,
+        game_state: &mut GameState,
+    ) {
+        if !self.is_entered {
+            self.enter(run, scores);
+        }
+
+        self.text_pos_x = lerp_e(
+            self.text_pos_x,
+            Self::TARGET_TEXT_POS,
+            delta_time,
+            Self::LERP_SPEED,
+            Self::LERP_ACCEPTABLE_ERR,
+        );
+
+        match self.screen_state {
+            GameOverState::NameEntry => self.handle_name_entry_update(rl, gd, run, scores),
+            GameOverState::Menu => self.handle_menu_update(rl, gd, locale, run, game_state),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw(
+        &self,
+        thread: &RaylibThread,
+        d: &mut RaylibDrawHandle,
+        gd: &GameData,
+        font: &Font,
+        locale: &Locale,
+        run: &RunData,
+        render_target: &mut RenderTexture2D,
+    ) {
+        const FONT_SIZE: f32 = 84f32;
+        const STAT_FONT_SIZE: f32 = 48f32;
+        const TEXT_GAP: f32 = 72f32;
+        const STAT_GAP: f32 = 48f32;
+        const INACTIVE_WHITE: Color = Color::new(255u8, 255u8, 255u8, 191u8);
+        const TEXT_POSITION: f32 = SCREEN_HEIGHT as f32 - 32f32;
+        const STAT_POSITION: f32 = 200f32;
+
+        // DRAW IN VIEWPORT
+        {
+            let mut d = d.begin_texture_mode(thread, render_target);
+            d.clear_background(Color::BLACK);
+
+            draw_text(
+                &mut d,
+                font,
+                locale.tr("game_over.title"),
+                Vector2::new(self.text_pos_x, 64f32),
+                &TextStyle::new(FONT_SIZE, Color::DARKMAGENTA),
+            );
+
+            // Statistics
+            {
+                let stats: [(&str, String); 6] = [
+                    (locale.tr("game_over.score"), format!("{}", run.score)),
+                    (
+                        locale.tr("game_over.difficulty"),
+                        locale.tr(run.difficulty.text_key()).to_string(),
+                    ),
+                    (locale.tr("game_over.stage"), format!("{}", run.stage)),
+                    (locale.tr("game_over.graze"), format!("{}", run.graze)),
+                    (
+                        locale.tr("game_over.bombs_used"),
+                        format!("{}", run.bombs_used),
+                    ),
+                    (locale.tr("game_over.misses"), format!("{}", run.misses)),
+                ];
+                for (i, (label, value)) in stats.iter().enumerate() {
+                    let y: f32 = STAT_POSITION + STAT_GAP * i as f32;
+                    draw_text(
+                        &mut d,
+                        font,
+                        label,
+                        Vector2::new(self.text_pos_x, y),
+                        &TextStyle::new(STAT_FONT_SIZE, INACTIVE_WHITE),
+                    );
+                    draw_text(
+                        &mut d,
+                        font,
+                        value,
+                        Vector2::new(self.text_pos_x + 300f32, y),
+                        &TextStyle::new(STAT_FONT_SIZE, Color::WHITE),
+                    );
+                }
+            }
+
+            match self.screen_state {
+                GameOverState::NameEntry => {
+                    draw_text(
+                        &mut d,
+                        font,
+                        locale.tr("game_over.new_high_score"),
+                        Vector2::new(self.text_pos_x, TEXT_POSITION - TEXT_GAP * 4f32),
+                        &TextStyle::new(FONT_SIZE, Color::WHITE),
+                    );
+                    let current_char: String = self.current_name_char(locale);
+                    draw_text(
+                        &mut d,
+                        font,
+                        &format!("{}[{}]", self.name, current_char),
+                        Vector2::new(self.text_pos_x, TEXT_POSITION - TEXT_GAP * 3f32),
+                        &TextStyle::new(FONT_SIZE, Color::WHITE),
+                    );
+                    draw_text(
+                        &mut d,
+                        font,
+                        locale.tr("game_over.name_hint"),
+                        Vector2::new(self.text_pos_x, TEXT_POSITION - TEXT_GAP * 1.5f32),
+                        &TextStyle::new(STAT_FONT_SIZE, INACTIVE_WHITE),
+                    );
+                }
+                GameOverState::Menu => {
+                    self.menu_page.draw(
+                        &mut d,
+                        font,
+                        locale,
+                        gd,
+                        MenuLayout {
+                            x: self.text_pos_x,
+                            x_mod: 0f32,
+                        },
+                    );
+                }
+            }
+        }
+        draw_on_target(d, render_target, gd.scaling_mode());
+    }
+
+    /// Resets screen for a freshly finished run
+    fn enter(&mut self, run: &RunData, scores: &HighScores) {
+        self.is_entered = true;
+        self.text_pos_x = Self::INITIAL_TEXT_POS;
+        self.replay_saved = false;
+        self.name.clear();
+        self.name_char_index = 0usize;
+        self.refresh_menu_page(run);
+        self.menu_page.choose(if run.continues_left() > 0 {
+            GameOverAction::Continue
+        } else {
+            GameOverAction::Retry
+        });
+        self.screen_state = if scores.qualifies(run.difficulty, run.score) {
+            GameOverState::NameEntry
+        } else {
+            GameOverState::Menu
+        };
+    }
+
+    /// Retry and Return to Title are always there
+    fn menu_page() -> MenuPage<GameOverAction> {
+        MenuPage::new(
+            vec![
+                MenuItem::button("game_over.continue", GameOverAction::Continue),
+                MenuItem::button("game_over.retry", GameOverAction::Retry),
+                MenuItem::button("game_over.save_replay", GameOverAction::SaveReplay),
+                MenuItem::button("game_over.return_to_title", GameOverAction::ReturnToTitle),
+            ],
+            0f32,
+            0f32,
+        )
+    }
+
+    /// Labels and enabled items follow continues left and the saved replay
+    fn refresh_menu_page(&mut self, run: &RunData) {
+        let continues_left: u8 = run.continues_left();
+        self.menu_page.set_label(
+            GameOverAction::Continue,
+            "game_over.continue",
+            &[continues_left.to_string()],
+        );
+        self.menu_page
+            .set_enabled(GameOverAction::Continue, continues_left > 0);
+        self.menu_page.set_label(
+            GameOverAction::SaveReplay,
+            if self.replay_saved {
+                "game_over.replay_saved"
+            } else {
+                "game_over.save_replay"
+            },
+            &[],
+        );
+        self.menu_page.set_enabled(
+            GameOverAction::SaveReplay,
+            run.can_save_replay() && !self.replay_saved,
+        );
+    }
+
+    /// Screen is entered anew next time GameOver state is reached
+    fn leave(&mut self, game_state: &mut GameState, next_game_state: GameState) {
+        self.is_entered = false;
+        *game_state = next_game_state;
+    }
+
+    fn current_name_char(&self, locale: &Locale) -> String {
+        match Self::NAME_CHARS.chars().nth(self.name_char_index) {
+            Some(c) => c.to_string(),
+            None => locale.tr("game_over.name_end").to_string(),
+        }
+    }
+
+    // NAME ENTRY
+    fn handle_name_entry_update(
+        &mut self,
+        rl: &RaylibHandle,
+        gd: &GameData,
+        run: &RunData,
+        scores: &mut HighScores,
+    ) {
+        // Chars plus the End entry
+        let chars_count: usize = Self::NAME_CHARS.chars().count() + 1;
+
+        if rl.is_key_pressed(gd.key("up")) || rl.is_key_pressed(gd.key("right")) {
+            self.name_char_index = (self.name_char_index + 1) % chars_count;
+        }
+        if rl.is_key_pressed(gd.key("down")) || rl.is_key_pressed(gd.key("left")) {
+            self.name_char_index = (self.name_char_index + chars_count - 1) % chars_count;
+        }
+        if rl.is_key_pressed(REJECT) || rl.is_key_pressed(gd.key("bomb")) {
+            self.name.pop();
+        }
+
+        let mut is_finished: bool = rl.is_key_pressed(ACCEPT);
+        if rl.is_key_pressed(gd.key("attack")) {
+            match Self::NAME_CHARS.chars().nth(self.name_char_index) {
+                Some(c) if self.name.len() < MAX_NAME_LENGTH => self.name.push(c),
+                Some(_) => {}
+                None => is_finished = true,
+            }
+        }
+
+        if is_finished {
+            if self.name.is_empty() {
+                self.name.push_str("Nameless");
+            }
+            scores.insert(ScoreEntry {
+                name: self.name.clone(),
+                score: run.score,
+                stage: run.stage,
+                difficulty: run.difficulty,
+            });
+            scores.save();
+            self.screen_state = GameOverState::Menu;
+        }
+    }
+
+    // MENU
+    fn handle_menu_update(
+        &mut self,
+        rl: &mut RaylibHandle,
+        gd: &mut GameData,
+        locale: &Locale,
+        run: &mut RunData,
+        game_state: &mut GameState,
+    ) {
+        self.refresh_menu_page(run);
+        match self.menu_page.update(rl, gd, locale) {
+            Some(GameOverAction::Continue) => {
+                run.use_continue();
+                self.leave(game_state, GameState::Playing);
+            }
+            Some(GameOverAction::Retry) => {
+                *run = RunData::new(run.difficulty);
+                self.leave(game_state, GameState::Playing);
+            }
+            Some(GameOverAction::SaveReplay) => {
+                self.replay_saved = run.replay.save().is_some();
+            }
+            Some(GameOverAction::ReturnToTitle) => {
+                self.leave(game_state, GameState::MainMenu);
+            }
+            None => {}
+        }
+    }
+}
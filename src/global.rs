@@ -1,20 +1,31 @@
 use raylib::prelude::*;
 use std::fs;
+use std::iter::Enumerate;
+use std::str::Lines;
+
+use crate::locale::*;
+use crate::user_dirs::*;
 
 // CONSTANTS
 pub const SCREEN_HEIGHT: i32 = 960;
 pub const SCREEN_WIDTH: i32 = 720;
 pub const MAIN_FONT: &[u8; 46020] = include_bytes!("../fonts/Catholicon.ttf");
-const OPTIONS_FILE_PATH: &str = "options.dat";
+pub const OPTIONS_FILE_NAME: &str = "options.dat";
 
 // DEFAULT VALUES
 // window
-const FULL_SCREEN: bool = false;
+const WINDOW_MODE: WindowMode = WindowMode::Windowed;
+const WINDOW_WIDTH: i32 = SCREEN_WIDTH * 3 / 4;
+const WINDOW_HEIGHT: i32 = SCREEN_HEIGHT * 3 / 4;
+const MONITOR: i32 = 0i32;
 const MAX_FPS: u32 = 60u32;
 const SHOULD_DRAW_FPS: bool = true;
 const VSYNC_ENABLED: bool = false;
 const BGM_VOLUME: f32 = 1.0f32;
 const SFX_VOLUME: f32 = 1.0f32;
+const LANGUAGE: &str = DEFAULT_LANGUAGE;
+const SCALING_MODE: ScalingMode = ScalingMode::Fit;
+const SCALE_FILTER: ScaleFilter = ScaleFilter::Bilinear;
 // keys
 const UP: KeyboardKey = KeyboardKey::KEY_UP;
 const DOWN: KeyboardKey = KeyboardKey::KEY_DOWN;
@@ -23,12 +34,14 @@ const RIGHT: KeyboardKey = KeyboardKey::KEY_RIGHT;
 const ATTACK: KeyboardKey = KeyboardKey::KEY_Z;
 const BOMB: KeyboardKey = KeyboardKey::KEY_X;
 const SLOW: KeyboardKey = KeyboardKey::KEY_LEFT_SHIFT;
+const SCREENSHOT: KeyboardKey = KeyboardKey::KEY_F12;
 
 // INPUT KEYS
 pub const ACCEPT: KeyboardKey = KeyboardKey::KEY_ENTER;
 pub const REJECT: KeyboardKey = KeyboardKey::KEY_BACKSPACE;
 
 // GAMESTATES
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum GameState {
     GreetingScreen, // Press enter
     MainMenu,       // Menu start quit settings, etc
@@ -37,48 +50,166 @@ pub enum GameState {
     EndScreen,      // Player won and titles are shown
 }
 
-// GLOBAL DATA
-pub struct GameData {
-    // Window vars
-    window_should_close: bool,
-    // Options
-    window_fullscreen: bool,
-    max_fps: u32,
-    should_draw_fps: bool,
-    vsync_enabled: bool,
-    
-    // Audio
-    audio: RaylibAudio,
-    bgm_volume: f32,
-    sfx_volume: f32,
+// WINDOW
+/// How the window takes the monitor
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum WindowMode {
+    Windowed,
+    Borderless, // Window without decorations over the whole monitor
+    Fullscreen, // Exclusive, monitor switches to window size
+}
+
+impl WindowMode {
+    /// Name used in options file and menu
+    pub fn as_str(&self) -> &str {
+        match self {
+            WindowMode::Windowed => "windowed",
+            WindowMode::Borderless => "borderless",
+            WindowMode::Fullscreen => "fullscreen",
+        }
+    }
+
+    /// Reverse of as_str(), returns None on unknown name.
+    /// Files of older versions have fullscreen as bool, which was borderless
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "windowed" | "false" => Some(WindowMode::Windowed),
+            "borderless" | "true" => Some(WindowMode::Borderless),
+            "fullscreen" => Some(WindowMode::Fullscreen),
+            _ => None,
+        }
+    }
+}
+
+/// Window sizes offered for the monitor: steps of the viewport size that fit on it,
+/// with some room left for window decorations and task bars
+pub fn window_sizes(monitor_width: i32, monitor_height: i32) -> Vec<(i32, i32)> {
+    const SCALES: [f32; 9] = [0.5f32, 0.75f32, 1f32, 1.25f32, 1.5f32, 2f32, 2.5f32, 3f32, 4f32];
+    const MONITOR_ROOM: f32 = 0.9f32;
+
+    let sizes: Vec<(i32, i32)> = SCALES
+        .iter()
+        .map(|scale| {
+            (
+                (SCREEN_WIDTH as f32 * scale) as i32,
+                (SCREEN_HEIGHT as f32 * scale) as i32,
+            )
+        })
+        .filter(|(width, height)| {
+            *width as f32 <= monitor_width as f32 * MONITOR_ROOM
+                && *height as f32 <= monitor_height as f32 * MONITOR_ROOM
+        })
+        .collect();
+    if sizes.is_empty() {
+        // Tiny monitor, smallest step is still better than nothing
+        return vec![(SCREEN_WIDTH / 2, SCREEN_HEIGHT / 2)];
+    }
+    sizes
+}
+
+// SCALING
+/// How the viewport is scaled into the window
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ScalingMode {
+    Fit,     // Largest size that keeps aspect ratio
+    Integer, // Whole multiples or divisors of the viewport size, keeps pixels square
+    Stretch, // Fills the whole window
+}
+
+impl ScalingMode {
+    /// Name used in options file and menu
+    pub fn as_str(&self) -> &str {
+        match self {
+            ScalingMode::Fit => "fit",
+            ScalingMode::Integer => "integer",
+            ScalingMode::Stretch => "stretch",
+        }
+    }
+
+    /// Reverse of as_str(), returns None on unknown name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "fit" => Some(ScalingMode::Fit),
+            "integer" => Some(ScalingMode::Integer),
+            "stretch" => Some(ScalingMode::Stretch),
+            _ => None,
+        }
+    }
+}
+
+/// Filtering of the viewport texture, nearest with integer scaling is pixel perfect
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ScaleFilter {
+    Bilinear,
+    Nearest,
+}
 
+impl ScaleFilter {
+    /// Name used in options file and menu
+    pub fn as_str(&self) -> &str {
+        match self {
+            ScaleFilter::Bilinear => "bilinear",
+            ScaleFilter::Nearest => "nearest",
+        }
+    }
+
+    /// Reverse of as_str(), returns None on unknown name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "bilinear" => Some(ScaleFilter::Bilinear),
+            "nearest" => Some(ScaleFilter::Nearest),
+            _ => None,
+        }
+    }
+
+    pub fn texture_filter(&self) -> TextureFilter {
+        match self {
+            ScaleFilter::Bilinear => TextureFilter::TEXTURE_FILTER_BILINEAR,
+            ScaleFilter::Nearest => TextureFilter::TEXTURE_FILTER_POINT,
+        }
+    }
+}
+
+// OPTIONS
+/// Everything the options file keeps, one value per line in the order of the fields
+#[derive(Clone, PartialEq, Debug)]
+pub struct Options {
+    pub window_mode: WindowMode,
+    pub max_fps: u32,
+    pub should_draw_fps: bool,
+    pub vsync_enabled: bool,
+    pub bgm_volume: f32,
+    pub sfx_volume: f32,
     // GameKeys
-    up: KeyboardKey,
-    down: KeyboardKey,
-    left: KeyboardKey,
-    right: KeyboardKey,
-    attack: KeyboardKey,
-    bomb: KeyboardKey,
-    slow: KeyboardKey,
+    pub up: KeyboardKey,
+    pub down: KeyboardKey,
+    pub left: KeyboardKey,
+    pub right: KeyboardKey,
+    pub attack: KeyboardKey,
+    pub bomb: KeyboardKey,
+    pub slow: KeyboardKey,
+    // Language code of the UI strings
+    pub language: String,
+    // Scaling of the viewport
+    pub scaling_mode: ScalingMode,
+    pub scale_filter: ScaleFilter,
+    pub monitor: i32,
+    // Windowed size and position, remembered between sessions. No position centers the window
+    pub window_size: (i32, i32),
+    pub window_position: Option<(i32, i32)>,
+    pub screenshot: KeyboardKey,
 }
 
-impl GameData {
+impl Options {
+    /// Default values
     pub fn new() -> Self {
         Self {
-            // Must not be changed outside window_must_close()
-            window_should_close: false,
-            // Window
-            window_fullscreen: FULL_SCREEN,
+            window_mode: WINDOW_MODE,
             max_fps: MAX_FPS,
             should_draw_fps: SHOULD_DRAW_FPS,
             vsync_enabled: VSYNC_ENABLED, // By default, there is no VSync
-            
-            // Audio
-            audio: RaylibAudio::init_audio_device().unwrap(),
             bgm_volume: BGM_VOLUME,
             sfx_volume: SFX_VOLUME,
-
-            // Keys
             up: UP,
             down: DOWN,
             left: LEFT,
@@ -86,6 +217,134 @@ impl GameData {
             attack: ATTACK,
             bomb: BOMB,
             slow: SLOW,
+            language: LANGUAGE.to_string(),
+            scaling_mode: SCALING_MODE,
+            scale_filter: SCALE_FILTER,
+            monitor: MONITOR,
+            window_size: (WINDOW_WIDTH, WINDOW_HEIGHT),
+            window_position: None,
+            screenshot: SCREENSHOT,
+        }
+    }
+
+    /// Options file that holds nothing but defaults, like the one older versions shipped with
+    pub fn is_default_file(path: &str, option_data: &str) -> bool {
+        Self::parse(path, option_data) == Ok(Self::new())
+    }
+
+    /// Parses options file, fails on the first required line that is missing or malformed.
+    /// Lines added after the first version are optional and fall back to defaults
+    pub fn parse(path: &str, option_data: &str) -> Result<Self, String> {
+        let mut lines = option_data.lines().enumerate();
+        let mut options: Options = Self::new();
+
+        // Window and audio
+        options.window_mode = next_option(&mut lines, path, WindowMode::from_name)?;
+        options.max_fps = next_option(&mut lines, path, |line| line.parse().ok())?;
+        options.should_draw_fps = next_option(&mut lines, path, |line| line.parse().ok())?;
+        options.vsync_enabled = next_option(&mut lines, path, |line| line.parse().ok())?;
+        options.bgm_volume = next_option(&mut lines, path, parse_volume)?;
+        options.sfx_volume = next_option(&mut lines, path, parse_volume)?;
+
+        // Keys
+        options.up = next_option(&mut lines, path, parse_key)?;
+        options.down = next_option(&mut lines, path, parse_key)?;
+        options.left = next_option(&mut lines, path, parse_key)?;
+        options.right = next_option(&mut lines, path, parse_key)?;
+        options.attack = next_option(&mut lines, path, parse_key)?;
+        options.bomb = next_option(&mut lines, path, parse_key)?;
+        options.slow = next_option(&mut lines, path, parse_key)?;
+
+        // Language
+        if let Some((_, line)) = lines.next() {
+            options.language = line.to_string();
+        }
+
+        // Scaling
+        let mut next_line = || lines.next().map(|(_, line)| line);
+        if let Some(mode) = next_line().and_then(ScalingMode::from_name) {
+            options.scaling_mode = mode;
+        }
+        if let Some(filter) = next_line().and_then(ScaleFilter::from_name) {
+            options.scale_filter = filter;
+        }
+
+        // Monitor and windowed size and position
+        let mut next_i32 = || next_line().and_then(|line| line.parse::<i32>().ok());
+        if let Some(monitor) = next_i32() {
+            options.monitor = monitor;
+        }
+        if let (Some(width), Some(height)) = (next_i32(), next_i32()) {
+            options.window_size = (width, height);
+        }
+        if let (Some(x), Some(y)) = (next_i32(), next_i32()) {
+            options.window_position = Some((x, y));
+        }
+
+        // Keys added after the first version
+        if let Some(key) = next_i32().and_then(key_from_i32) {
+            options.screenshot = key;
+        }
+        Ok(options)
+    }
+
+    /// Reverse of parse()
+    pub fn file_data(&self) -> String {
+        // Empty position lines leave the window centered
+        let (x, y): (String, String) = match self.window_position {
+            Some((x, y)) => (x.to_string(), y.to_string()),
+            None => (String::new(), String::new()),
+        };
+        format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n",
+            self.window_mode.as_str(),
+            self.max_fps,
+            self.should_draw_fps,
+            self.vsync_enabled,
+            self.bgm_volume,
+            self.sfx_volume,
+            self.up as u32,
+            self.down as u32,
+            self.left as u32,
+            self.right as u32,
+            self.attack as u32,
+            self.bomb as u32,
+            self.slow as u32,
+            self.language,
+            self.scaling_mode.as_str(),
+            self.scale_filter.as_str(),
+            self.monitor,
+            self.window_size.0,
+            self.window_size.1,
+            x,
+            y,
+            self.screenshot as u32
+        )
+    }
+}
+
+// GLOBAL DATA
+pub struct GameData {
+    // Window vars
+    window_should_close: bool,
+    // Options file, can be changed from command line
+    config_path: String,
+    options: Options,
+
+    // Audio
+    audio: RaylibAudio,
+}
+
+impl GameData {
+    pub fn new() -> Self {
+        Self {
+            // Must not be changed outside window_must_close()
+            window_should_close: false,
+            config_path: config_path(OPTIONS_FILE_NAME),
+            options: Options::new(),
+
+            // Audio
+            audio: RaylibAudio::init_audio_device().unwrap(),
         }
     }
 
@@ -100,58 +359,152 @@ impl GameData {
         self.window_should_close
     }
 
-    /// Toggle Fullscreen using gamedata, returns fullscreen state in written in Data
-    pub fn toggle_fullscreen(&mut self, rl: &mut RaylibHandle) {
-        self.window_fullscreen = !self.window_fullscreen;
-        rl.toggle_borderless_windowed();
+    /// Switches window to windowed, borderless or exclusive fullscreen on chosen monitor
+    pub fn set_window_mode(&mut self, rl: &mut RaylibHandle, mode: WindowMode) {
+        if mode != self.options.window_mode {
+            self.leave_window_mode(rl);
+            self.options.window_mode = mode;
+            self.enter_window_mode(rl);
+        }
+        self.save_config();
+    }
+
+    pub fn window_mode(&self) -> WindowMode {
+        self.options.window_mode
+    }
+
+    /// Sets windowed size, window is centered on its monitor
+    pub fn set_window_size(&mut self, rl: &mut RaylibHandle, width: i32, height: i32) {
+        self.options.window_size = (width, height);
+        self.options.window_position = None;
+        if self.options.window_mode == WindowMode::Windowed {
+            self.enter_window_mode(rl);
+        }
         self.save_config();
     }
 
-    /// Returns window_fullscreen, from game data
-    pub fn is_fullscreen(&self) -> bool {
-        self.window_fullscreen
+    pub fn window_size(&self) -> (i32, i32) {
+        self.options.window_size
+    }
+
+    /// Moves window to the monitor, out of range monitor is ignored
+    pub fn set_monitor(&mut self, rl: &mut RaylibHandle, monitor: i32) {
+        if !(0..get_monitor_count()).contains(&monitor) {
+            log_warn!("Monitor {} is not connected", monitor);
+            return;
+        }
+        self.leave_window_mode(rl);
+        self.options.monitor = monitor;
+        self.options.window_position = None;
+        self.enter_window_mode(rl);
+        self.save_config();
+    }
+
+    pub fn monitor(&self) -> i32 {
+        self.options.monitor
+    }
+
+    /// Remembers size and position the player gave to the window, saved on exit
+    pub fn remember_window(&mut self, rl: &RaylibHandle) {
+        if self.options.window_mode != WindowMode::Windowed
+            || rl.is_window_minimized()
+            || rl.is_window_maximized()
+        {
+            return;
+        }
+        let position: Vector2 = rl.get_window_position();
+        self.options.window_size = (rl.get_screen_width(), rl.get_screen_height());
+        self.options.window_position = Some((position.x as i32, position.y as i32));
+    }
+
+    /// Window goes back to windowed, which is how it was created
+    fn leave_window_mode(&mut self, rl: &mut RaylibHandle) {
+        match self.options.window_mode {
+            WindowMode::Windowed => {}
+            WindowMode::Borderless => rl.toggle_borderless_windowed(),
+            WindowMode::Fullscreen => rl.toggle_fullscreen(),
+        }
+    }
+
+    /// Applies window mode from windowed
+    fn enter_window_mode(&mut self, rl: &mut RaylibHandle) {
+        let monitor: i32 = self.options.monitor;
+        match self.options.window_mode {
+            WindowMode::Windowed => {
+                let (width, height) = self.options.window_size;
+                let (x, y) = *self
+                    .options
+                    .window_position
+                    .get_or_insert_with(|| centered_position(monitor, (width, height)));
+                rl.set_window_size(width, height);
+                rl.set_window_position(x, y);
+            }
+            WindowMode::Borderless => {
+                rl.set_window_monitor(monitor);
+                rl.toggle_borderless_windowed();
+            }
+            WindowMode::Fullscreen => {
+                rl.set_window_monitor(monitor);
+                rl.set_window_size(get_monitor_width(monitor), get_monitor_height(monitor));
+                rl.toggle_fullscreen();
+            }
+        }
+    }
+
+    /* Options file */
+    /// Options are read from and saved to this file, must be set before load_config
+    pub fn set_config_path(&mut self, path: &str) {
+        self.config_path = path.to_string();
+    }
+
+    pub fn config_path(&self) -> &str {
+        &self.config_path
     }
 
     /* FPS Cap */
     /// Sets a max frame rate
     pub fn set_max_fps(&mut self, rl: &mut RaylibHandle, new_max_fps: u32) {
-        self.max_fps = new_max_fps;
-        rl.set_target_fps(self.max_fps);
+        self.options.max_fps = new_max_fps;
+        rl.set_target_fps(self.options.max_fps);
         self.save_config();
     }
 
     /// Returns current fps cap
     pub fn get_max_fps(&self) -> u32 {
-        self.max_fps
+        self.options.max_fps
     }
 
     /* FPS Draw */
     /// Toggles is fps should be drawn
     pub fn fps_should_draw_toggle(&mut self) {
-        self.should_draw_fps = !self.should_draw_fps;
+        self.options.should_draw_fps = !self.options.should_draw_fps;
         self.save_config();
     }
 
     /// Returns if fps should be drawn
     pub fn fps_should_draw(&self) -> bool {
-        self.should_draw_fps
+        self.options.should_draw_fps
     }
 
     /* V-Sync */
     /// Toggle V-Sync
     pub fn toggle_vsync(&mut self, rl: &mut RaylibHandle) {
-        self.vsync_enabled = !self.vsync_enabled;
+        self.options.vsync_enabled = !self.options.vsync_enabled;
+        self.apply_vsync(rl);
+        self.save_config();
+    }
+
+    fn apply_vsync(&self, rl: &mut RaylibHandle) {
         if self.is_vsync_enabled() {
             rl.set_window_state(WindowState::set_vsync_hint(rl.get_window_state(), true));
         } else {
             rl.clear_window_state(WindowState::set_vsync_hint(rl.get_window_state(), true));
         }
-        self.save_config();
     }
 
     /// Returns true if vsync is enabled
     pub fn is_vsync_enabled(&self) -> bool {
-        self.vsync_enabled
+        self.options.vsync_enabled
     }
 
     /* Audio */
@@ -169,18 +522,18 @@ impl GameData {
                 new_volume
             )
         }
+        self.options.bgm_volume = new_volume;
         self.save_config();
-        self.bgm_volume = new_volume
     }
 
     /// Returns current bgm volume
     pub fn get_bgm_volume(&self) -> f32 {
-        self.bgm_volume
+        self.options.bgm_volume
     }
 
     /// Returns current bgm volume in percents
     pub fn get_bgm_volume_prc(&self) -> f32 {
-        (self.bgm_volume * 100f32).round()
+        (self.options.bgm_volume * 100f32).round()
     }
 
     /// Set sound effects volume
@@ -191,66 +544,70 @@ impl GameData {
                 new_volume
             )
         }
+        self.options.sfx_volume = new_volume;
         self.save_config();
-        self.sfx_volume = new_volume
     }
 
     /// Returns sfx volume
     pub fn get_sfx_volume(&self) -> f32 {
-        self.sfx_volume
+        self.options.sfx_volume
     }
 
     /// Returns sfx volume in percents
     pub fn get_sfx_volume_prc(&self) -> f32 {
-        (self.sfx_volume * 100f32).round()
+        (self.options.sfx_volume * 100f32).round()
     }
 
-    /// Resets All gamedata.option values
-    pub fn reset_options(&mut self, rl: &mut RaylibHandle) {
-        // window
-        if self.window_fullscreen != FULL_SCREEN {
-            self.toggle_fullscreen(rl);
-        }
-        if self.max_fps != MAX_FPS {
-            self.set_max_fps(rl, MAX_FPS);
-        }
-        if self.should_draw_fps != SHOULD_DRAW_FPS {
-            self.fps_should_draw_toggle();
-        }
-        if self.vsync_enabled != VSYNC_ENABLED {
-            self.toggle_vsync(rl);
-        }
-        if self.bgm_volume != BGM_VOLUME {
-            self.set_bgm_volume(BGM_VOLUME);
-        }
-        if self.sfx_volume != SFX_VOLUME {
-            self.set_sfx_volume(SFX_VOLUME);
-        }
+    /* Language */
+    /// Sets language of the UI strings by its code
+    pub fn set_language(&mut self, code: &str) {
+        self.options.language = code.to_string();
+        self.save_config();
+    }
 
-        // keys
-        self.up = UP;
-        self.down = DOWN;
-        self.left = LEFT;
-        self.right = RIGHT;
-        self.attack = ATTACK;
-        self.bomb = BOMB;
-        self.slow = SLOW;
+    /// Returns language code
+    pub fn language(&self) -> &str {
+        &self.options.language
+    }
 
-        rl.set_window_size(320, 320);
+    /* Scaling */
+    /// Sets how the viewport is scaled into the window
+    pub fn set_scaling_mode(&mut self, mode: ScalingMode) {
+        self.options.scaling_mode = mode;
+        self.save_config();
+    }
 
+    pub fn scaling_mode(&self) -> ScalingMode {
+        self.options.scaling_mode
+    }
+
+    /// Sets filtering of the viewport, applied by main loop
+    pub fn set_scale_filter(&mut self, filter: ScaleFilter) {
+        self.options.scale_filter = filter;
         self.save_config();
     }
 
-    /// Keys Data loaded in gamedata. Provide with action: "up", "down", "left", "right", "attack", "bomb", "slow"
+    pub fn scale_filter(&self) -> ScaleFilter {
+        self.options.scale_filter
+    }
+
+    /// Resets All gamedata.option values
+    pub fn reset_options(&mut self, rl: &mut RaylibHandle) {
+        self.apply_options(rl, Options::new(), false);
+        self.save_config();
+    }
+
+    /// Keys Data loaded in gamedata. Provide with action: "up", "down", "left", "right", "attack", "bomb", "slow", "screenshot"
     pub fn key(&self, action: &str) -> KeyboardKey {
         match action {
-            "up" => self.up,
-            "down" => self.down,
-            "left" => self.left,
-            "right" => self.right,
-            "attack" => self.attack,
-            "bomb" => self.bomb,
-            "slow" => self.slow,
+            "up" => self.options.up,
+            "down" => self.options.down,
+            "left" => self.options.left,
+            "right" => self.options.right,
+            "attack" => self.options.attack,
+            "bomb" => self.options.bomb,
+            "slow" => self.options.slow,
+            "screenshot" => self.options.screenshot,
             _ => panic!(
                 "Action '{}' does not exist! Refer to list of actions in the global.rs file",
                 action
@@ -259,16 +616,17 @@ impl GameData {
     }
 
     // FIXME: Make a check, so no important keys go overwritten
-    /// Update KeyData from settings. Provide with action: "up", "down", "left", "right", "attack", "bomb", "slow"
+    /// Update KeyData from settings. Provide with action: "up", "down", "left", "right", "attack", "bomb", "slow", "screenshot"
     pub fn set_key(&mut self, action: &str, new_key: KeyboardKey) {
         match action {
-            "up" => self.up = new_key,
-            "down" => self.down = new_key,
-            "left" => self.left = new_key,
-            "right" => self.right = new_key,
-            "attack" => self.attack = new_key,
-            "bomb" => self.bomb = new_key,
-            "slow" => self.slow = new_key,
+            "up" => self.options.up = new_key,
+            "down" => self.options.down = new_key,
+            "left" => self.options.left = new_key,
+            "right" => self.options.right = new_key,
+            "attack" => self.options.attack = new_key,
+            "bomb" => self.options.bomb = new_key,
+            "slow" => self.options.slow = new_key,
+            "screenshot" => self.options.screenshot = new_key,
             _ => panic!("Action '{}' does not exist!", action),
         }
         self.save_config();
@@ -281,103 +639,123 @@ impl GameData {
         key
     }
 
+    /// Options in a readable form for crash reports
+    pub fn summary(&self) -> String {
+        let options: &Options = &self.options;
+        format!(
+            "Window: {} {}x{} on monitor {}\nScaling: {}, {}\nMax fps: {}, vsync: {}\nLanguage: {}\nVolume: bgm {}, sfx {}\nConfig: {}",
+            options.window_mode.as_str(),
+            options.window_size.0,
+            options.window_size.1,
+            options.monitor,
+            options.scaling_mode.as_str(),
+            options.scale_filter.as_str(),
+            options.max_fps,
+            options.vsync_enabled,
+            options.language,
+            options.bgm_volume,
+            options.sfx_volume,
+            self.config_path
+        )
+    }
+
     pub fn save_config(&self) {
-        let option_data: String = format!(
-            "{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n",
-            self.window_fullscreen,
-            self.max_fps,
-            self.should_draw_fps,
-            self.vsync_enabled,
-            self.bgm_volume,
-            self.sfx_volume,
-            self.up as u32,
-            self.down as u32,
-            self.left as u32,
-            self.right as u32,
-            self.attack as u32,
-            self.bomb as u32,
-            self.slow as u32
-        );
-        fs::write(OPTIONS_FILE_PATH, option_data.as_bytes()).ok();
+        fs::write(&self.config_path, self.options.file_data().as_bytes()).ok();
     }
 
     // FIXME: All loaded settings must apply, same as in reset 1/2 Keys must have own option screen
     pub fn load_config(&mut self, rl: &mut RaylibHandle) {
-        if !fs::exists(OPTIONS_FILE_PATH).unwrap() {
-            println!(
-                "{} didn't existed so it is being created, with defaults",
-                OPTIONS_FILE_PATH
-            );
-            self.reset_options(rl);
-        }
-        let option_data: String = fs::read_to_string(OPTIONS_FILE_PATH).unwrap();
-        let mut lines = option_data.lines();
-
-        // Window
-        self.window_fullscreen = lines.next().unwrap().parse().unwrap();
-        self.max_fps = lines.next().unwrap().parse().unwrap();
-        self.should_draw_fps = lines.next().unwrap().parse().unwrap();
-        self.vsync_enabled = lines.next().unwrap().parse().unwrap(); // By default, there is no VSync
-        self.bgm_volume = lines.next().unwrap().parse().unwrap();
-        self.sfx_volume = lines.next().unwrap().parse().unwrap();
-
-        // Applying settings
-        if self.window_fullscreen != FULL_SCREEN {
-            rl.toggle_borderless_windowed();
-        }
-        if self.max_fps != MAX_FPS {
-            self.set_max_fps(rl, self.max_fps);
-        }
-        if self.should_draw_fps != SHOULD_DRAW_FPS {
-            self.should_draw_fps = false;
-        }
-        if self.vsync_enabled != VSYNC_ENABLED {
-            rl.set_window_state(WindowState::set_vsync_hint(rl.get_window_state(), true));
-        }
-        if self.bgm_volume != BGM_VOLUME {
-            self.set_bgm_volume(self.bgm_volume);
+        let options: Option<Options> = match fs::read_to_string(&self.config_path) {
+            Ok(option_data) => match Options::parse(&self.config_path, &option_data) {
+                Ok(options) => Some(options),
+                Err(err) => {
+                    log_error!("{}, options are reset to defaults", err);
+                    None
+                }
+            },
+            Err(err) => {
+                log_info!(
+                    "{}: {}, it is being created with defaults",
+                    self.config_path,
+                    err
+                );
+                None
+            }
+        };
+        // Window was created before options were known, so it is placed in any case
+        match options {
+            Some(options) => self.apply_options(rl, options, true),
+            None => {
+                self.apply_options(rl, Options::new(), true);
+                self.save_config();
+            }
         }
-        if self.sfx_volume != SFX_VOLUME {
-            self.set_sfx_volume(self.sfx_volume);
-        }
-
-        // Keys
-        self.up = key_from_i32(lines.next().unwrap().parse().unwrap()).unwrap();
-        self.down = key_from_i32(lines.next().unwrap().parse().unwrap()).unwrap();
-        self.left = key_from_i32(lines.next().unwrap().parse().unwrap()).unwrap();
-        self.right = key_from_i32(lines.next().unwrap().parse().unwrap()).unwrap();
-        self.attack = key_from_i32(lines.next().unwrap().parse().unwrap()).unwrap();
-        self.bomb = key_from_i32(lines.next().unwrap().parse().unwrap()).unwrap();
-        self.slow = key_from_i32(lines.next().unwrap().parse().unwrap()).unwrap();
     }
-}
-
-/// DRAW TEXTURE TARGET
-/// Draws viewport saving its dpi
-pub fn draw_on_target(d: &mut RaylibDrawHandle, render_target: &RenderTexture2D) {
-    // Screen scaling
-    let mut scaling: f32 = 1f32;
 
-    let scale_x: f32 = d.get_screen_width() as f32 / SCREEN_WIDTH as f32;
-    let scale_y: f32 = d.get_screen_height() as f32 / SCREEN_HEIGHT as f32;
+    /// Re-reads options file while the game runs, nothing is applied if it is malformed.
+    /// Values are not saved back, so the file is not touched by its own reload
+    pub fn reload_config(&mut self, rl: &mut RaylibHandle) -> Result<(), String> {
+        let option_data: String = fs::read_to_string(&self.config_path)
+            .map_err(|err| format!("{}: {}", self.config_path, err))?;
+        let options: Options = Options::parse(&self.config_path, &option_data)?;
+        self.apply_options(rl, options, false);
+        Ok(())
+    }
 
-    if scale_x != scaling && scale_y != scaling {
-        if scale_x >= scale_y {
-            scaling = scale_y;
-        } else {
-            scaling = scale_x;
+    /// Takes options over. Window, frame rate and vsync are only touched when they differ
+    /// or the window is placed in any case, monitor that is not connected falls back to the default one
+    fn apply_options(
+        &mut self,
+        rl: &mut RaylibHandle,
+        mut options: Options,
+        should_place_window: bool,
+    ) {
+        if !(0..get_monitor_count()).contains(&options.monitor) {
+            log_warn!("Monitor {} is not connected", options.monitor);
+            options.monitor = MONITOR;
+            options.window_position = None;
+        }
+        let is_window_changed: bool = should_place_window
+            || options.window_mode != self.options.window_mode
+            || options.monitor != self.options.monitor
+            || options.window_size != self.options.window_size
+            || options.window_position != self.options.window_position;
+        let is_fps_changed: bool = options.max_fps != self.options.max_fps;
+        let is_vsync_changed: bool = options.vsync_enabled != self.options.vsync_enabled;
+
+        if is_window_changed {
+            self.leave_window_mode(rl);
+        }
+        self.options = options;
+        if is_window_changed {
+            self.enter_window_mode(rl);
+        }
+        if is_fps_changed {
+            rl.set_target_fps(self.options.max_fps);
+        }
+        if is_vsync_changed {
+            self.apply_vsync(rl);
         }
     }
+}
 
-    let screen_center: Vector2 = Vector2::new(
-        d.get_screen_width() as f32 / 2f32,
-        d.get_screen_height() as f32 / 2f32,
-    );
+/// Top left corner of a window of the size in the middle of the monitor
+fn centered_position(monitor: i32, size: (i32, i32)) -> (i32, i32) {
+    let monitor_position: Vector2 = get_monitor_position(monitor);
+    (
+        monitor_position.x as i32 + (get_monitor_width(monitor) - size.0) / 2,
+        monitor_position.y as i32 + (get_monitor_height(monitor) - size.1) / 2,
+    )
+}
 
-    let render_target_position: Vector2 = Vector2::new(
-        screen_center.x - render_target.texture.width as f32 * scaling / 2f32,
-        screen_center.y - render_target.texture.height as f32 * scaling / 2f32,
-    );
+/// DRAW TEXTURE TARGET
+/// Draws viewport into the rectangle given by scaling mode
+pub fn draw_on_target(
+    d: &mut RaylibDrawHandle,
+    render_target: &RenderTexture2D,
+    scaling_mode: ScalingMode,
+) {
+    let target: Rectangle = target_rect(scaling_mode, d.get_screen_width(), d.get_screen_height());
 
     d.draw_texture_pro(
         render_target.texture(),
@@ -387,18 +765,80 @@ pub fn draw_on_target(d: &mut RaylibDrawHandle, render_target: &RenderTexture2D)
             render_target.texture.width,
             -render_target.texture.height,
         ),
-        rrect(
-            0,
-            0,
-            render_target.texture.width as f32 * scaling,
-            render_target.texture.height as f32 * scaling,
-        ),
-        rvec2(-render_target_position.x, -render_target_position.y),
+        target,
+        rvec2(0f32, 0f32),
         0f32,
         Color::WHITE,
     );
 }
 
+/// Where the viewport is drawn in the window, centered unless it is stretched
+pub fn target_rect(scaling_mode: ScalingMode, screen_width: i32, screen_height: i32) -> Rectangle {
+    let scale_x: f32 = screen_width as f32 / SCREEN_WIDTH as f32;
+    let scale_y: f32 = screen_height as f32 / SCREEN_HEIGHT as f32;
+    let fit_scaling: f32 = scale_x.min(scale_y);
+
+    let (width, height): (f32, f32) = match scaling_mode {
+        ScalingMode::Fit => (
+            SCREEN_WIDTH as f32 * fit_scaling,
+            SCREEN_HEIGHT as f32 * fit_scaling,
+        ),
+        ScalingMode::Integer => {
+            // Window smaller than viewport gets 1/2, 1/3... so pixels stay even
+            let scaling: f32 = if fit_scaling >= 1f32 {
+                fit_scaling.floor()
+            } else if fit_scaling > 0f32 {
+                1f32 / (1f32 / fit_scaling).ceil()
+            } else {
+                0f32
+            };
+            (SCREEN_WIDTH as f32 * scaling, SCREEN_HEIGHT as f32 * scaling)
+        }
+        ScalingMode::Stretch => (screen_width as f32, screen_height as f32),
+    };
+
+    // Whole pixels, so nearest filter has no seams
+    Rectangle::new(
+        ((screen_width as f32 - width) / 2f32).floor(),
+        ((screen_height as f32 - height) / 2f32).floor(),
+        width,
+        height,
+    )
+}
+
+/// POINTER
+/// Mouse or first touch position in viewport space, None if it is out of the viewport
+pub fn pointer_position(rl: &RaylibHandle, gd: &GameData) -> Option<Vector2> {
+    let position: Vector2 = if rl.get_touch_point_count() > 0 {
+        rl.get_touch_position(0)
+    } else {
+        rl.get_mouse_position()
+    };
+    let target: Rectangle = target_rect(
+        gd.scaling_mode(),
+        rl.get_screen_width(),
+        rl.get_screen_height(),
+    );
+    if !target.check_collision_point_rec(position) {
+        return None;
+    }
+    Some(Vector2::new(
+        (position.x - target.x) * SCREEN_WIDTH as f32 / target.width,
+        (position.y - target.y) * SCREEN_HEIGHT as f32 / target.height,
+    ))
+}
+
+/// Pointer moved since last frame, so hover may take the choice from keyboard
+pub fn pointer_moved(rl: &RaylibHandle) -> bool {
+    let delta: Vector2 = rl.get_mouse_delta();
+    delta.x != 0f32 || delta.y != 0f32 || rl.get_touch_point_count() > 0
+}
+
+/// Left click or tap
+pub fn pointer_pressed(rl: &RaylibHandle) -> bool {
+    rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT)
+}
+
 /// lerp that handles appropriate error, so no infinite interpolation, also delta time
 pub fn lerp_e(v0: f32, v1: f32, delta_time: &f32, lerp_speed: f32, err_size: f32) -> f32 {
     if !compare_floats(v0, v1, err_size) {
@@ -415,3 +855,139 @@ pub fn compare_floats(v0: f32, v1: f32, err_size: f32) -> bool {
     }
     true
 }
+
+/// Next required line of the options file, error names the line that is wrong
+fn next_option<T>(
+    lines: &mut Enumerate<Lines>,
+    path: &str,
+    parse: impl Fn(&str) -> Option<T>,
+) -> Result<T, String> {
+    let (index, line) = lines
+        .next()
+        .ok_or_else(|| format!("{}: file ends too early", path))?;
+    parse(line).ok_or_else(|| format!("{}:{}: can't parse '{}'", path, index + 1, line))
+}
+
+fn parse_volume(line: &str) -> Option<f32> {
+    line.parse()
+        .ok()
+        .filter(|volume| (0f32..=1f32).contains(volume))
+}
+
+fn parse_key(line: &str) -> Option<KeyboardKey> {
+    line.parse().ok().and_then(key_from_i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATH: &str = "options.dat";
+
+    #[test]
+    fn options_survive_their_file() {
+        let mut options: Options = Options::new();
+        assert_eq!(Options::parse(PATH, &options.file_data()), Ok(options.clone()));
+        options.window_mode = WindowMode::Fullscreen;
+        options.bgm_volume = 0.25f32;
+        options.language = "de".to_string();
+        options.window_position = Some((-40i32, 300i32));
+        options.screenshot = KeyboardKey::KEY_F9;
+        assert_eq!(Options::parse(PATH, &options.file_data()), Ok(options));
+    }
+
+    #[test]
+    fn first_version_files_get_defaults_for_newer_lines() {
+        let data: String = Options::new()
+            .file_data()
+            .lines()
+            .take(13)
+            .map(|line| format!("{}\n", line))
+            .collect();
+        assert_eq!(Options::parse(PATH, &data), Ok(Options::new()));
+    }
+
+    #[test]
+    fn shipped_options_file_is_default() {
+        // options.dat that was in the repository before files moved to user directories
+        let shipped: &str = "windowed\n60\ntrue\nfalse\n1\n1\n\
+                             265\n264\n263\n262\n90\n88\n340\n\
+                             en\nfit\nbilinear\n";
+        assert!(Options::is_default_file(PATH, shipped));
+        assert!(!Options::is_default_file(PATH, &shipped.replacen("en", "de", 1)));
+        assert!(!Options::is_default_file(PATH, ""));
+    }
+
+    #[test]
+    fn malformed_required_lines_are_rejected() {
+        let data: String = Options::new().file_data();
+        let short: String = data.lines().take(12).collect::<Vec<&str>>().join("\n");
+        assert!(Options::parse(PATH, &short).is_err());
+        let loud: String = data.replacen("\n1\n", "\n1.5\n", 1);
+        assert_eq!(
+            Options::parse(PATH, &loud),
+            Err(format!("{}:5: can't parse '1.5'", PATH))
+        );
+    }
+
+    // Rectangle has no PartialEq
+    fn rect(scaling_mode: ScalingMode, width: i32, height: i32) -> [f32; 4] {
+        let rect: Rectangle = target_rect(scaling_mode, width, height);
+        [rect.x, rect.y, rect.width, rect.height]
+    }
+
+    #[test]
+    fn fit_keeps_aspect_ratio_and_centers() {
+        assert_eq!(
+            rect(ScalingMode::Fit, 1440i32, 960i32),
+            [360f32, 0f32, 720f32, 960f32]
+        );
+        assert_eq!(
+            rect(ScalingMode::Fit, 1920i32, 1920i32),
+            [240f32, 0f32, 1440f32, 1920f32]
+        );
+    }
+
+    #[test]
+    fn integer_uses_whole_multiples_above_one() {
+        assert_eq!(
+            rect(ScalingMode::Integer, 1600i32, 2000i32),
+            [80f32, 40f32, 1440f32, 1920f32]
+        );
+        assert_eq!(
+            rect(ScalingMode::Integer, 1000i32, 1000i32),
+            [140f32, 20f32, 720f32, 960f32]
+        );
+    }
+
+    #[test]
+    fn integer_uses_divisors_below_one() {
+        assert_eq!(
+            rect(ScalingMode::Integer, 400i32, 500i32),
+            [20f32, 10f32, 360f32, 480f32]
+        );
+        assert_eq!(
+            rect(ScalingMode::Integer, 300i32, 300i32),
+            [60f32, 30f32, 180f32, 240f32]
+        );
+    }
+
+    #[test]
+    fn stretch_fills_window() {
+        assert_eq!(
+            rect(ScalingMode::Stretch, 500i32, 300i32),
+            [0f32, 0f32, 500f32, 300f32]
+        );
+    }
+
+    #[test]
+    fn zero_size_window_gets_empty_rect() {
+        for scaling_mode in [ScalingMode::Fit, ScalingMode::Integer, ScalingMode::Stretch] {
+            assert_eq!(rect(scaling_mode, 0i32, 0i32), [0f32; 4]);
+        }
+        assert_eq!(
+            rect(ScalingMode::Integer, 0i32, 600i32),
+            [0f32, 300f32, 0f32, 0f32]
+        );
+    }
+}
@@ -1,5 +1,10 @@
 use crate::global::*;
+use crate::locale::*;
+use crate::shaders::*;
+use crate::text::*;
 use raylib::prelude::*;
+const GREET_FOG: Fog = Fog::new(Color::DARKGRAY, 0.05f32);
+
 pub struct GreetScreen {
     timer_loading: f32,
     is_loaded: bool,
@@ -29,58 +34,63 @@ impl GreetScreen {
             self.is_loaded = true;
         }
 
-        if rl.is_key_pressed(KeyboardKey::KEY_ENTER) && self.is_loaded {
+        if (rl.is_key_pressed(KeyboardKey::KEY_ENTER) || pointer_pressed(rl)) && self.is_loaded {
             *game_state = GameState::MainMenu;
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn draw(
         &self,
         thread: &RaylibThread,
         d: &mut RaylibDrawHandle,
+        gd: &GameData,
         font: &Font,
+        locale: &Locale,
         cam: &Camera3D,
+        shaders: &mut ShaderManager,
         render_target: &mut RenderTexture2D,
     ) {
+        shaders.set_fog(&GREET_FOG);
+
         // DRAW IN VIEWPORT
         {
             let mut d = d.begin_texture_mode(thread, render_target);
             d.clear_background(Color::DARKGRAY);
             // Example Text
-            d.draw_text_ex(
+            draw_text(
+                &mut d,
                 font,
-                "Greeting",
+                locale.tr("greet.title"),
                 Vector2::new(12f32, 12f32),
-                22f32,
-                1f32,
-                Color::ORANGE,
+                &TextStyle::new(22f32, Color::ORANGE),
             );
             // DRAW 3D BG
             {
                 let mut d = d.begin_mode3D(*cam);
-                d.draw_grid(16i32, 1f32);
+                match shaders.get_mut(FOG_SHADER) {
+                    Some(fog_shader) => {
+                        let mut d = d.begin_shader_mode(fog_shader);
+                        d.draw_grid(16i32, 1f32);
+                    }
+                    None => d.draw_grid(16i32, 1f32),
+                }
             }
             if self.is_loaded {
-                const TEXT_POSITION: Vector2 = Vector2::new(140f32, SCREEN_HEIGHT as f32 - 100f32);
-                const TEXT_SIZE: f32 = 72f32;
-                d.draw_text_ex(
-                    font,
-                    "Press Enter to start",
-                    TEXT_POSITION + 2f32,
-                    TEXT_SIZE,
-                    1f32,
-                    Color::BLACK,
-                );
-                d.draw_text_ex(
+                const TEXT_POSITION: Vector2 =
+                    Vector2::new(SCREEN_WIDTH as f32 / 2f32, SCREEN_HEIGHT as f32 - 100f32);
+                const TEXT_STYLE: TextStyle = TextStyle::new(72f32, Color::DARKMAGENTA)
+                    .align(Align::Center)
+                    .shadow(2f32, Color::BLACK);
+                draw_text(
+                    &mut d,
                     font,
-                    "Press Enter to start",
+                    locale.tr("greet.press_start"),
                     TEXT_POSITION,
-                    TEXT_SIZE,
-                    1f32,
-                    Color::DARKMAGENTA,
+                    &TEXT_STYLE,
                 );
             }
         }
-        draw_on_target(d, render_target);
+        draw_on_target(d, render_target, gd.scaling_mode());
     }
 }
@@ -0,0 +1,158 @@
+use raylib::prelude::*;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::time::SystemTime;
+
+use crate::text::*;
+
+// Developer mode is on when this variable is set to anything but "0"
+const DEV_MODE_ENV: &str = "NMF_DEV";
+
+/// Developer mode file watcher. Polls modification times of watched files and
+/// directories and keeps reload errors for the on-screen overlay
+pub struct HotReload {
+    is_enabled: bool,
+    watched: Vec<String>,
+    modified: HashMap<String, SystemTime>,
+    timer: f32,
+    // Path and error text of reloads that failed
+    errors: Vec<(String, String)>,
+}
+
+impl HotReload {
+    const POLL_INTERVAL: f32 = 0.5f32;
+    const FONT_SIZE: f32 = 28f32;
+    const LINE_GAP: f32 = 30f32;
+    const MARGIN: f32 = 8f32;
+
+    pub fn new() -> Self {
+        let is_enabled: bool = is_dev_mode();
+        if is_enabled {
+            log_info!("Developer mode: hot reload is on");
+        }
+        Self {
+            is_enabled,
+            watched: Vec::new(),
+            modified: HashMap::new(),
+            timer: 0f32,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Adds file or directory to the watch list, directories are not walked recursively
+    pub fn watch(&mut self, path: &str) {
+        if !self.is_enabled {
+            return;
+        }
+        self.watched.push(path.to_string());
+        // Files that exist now are not reported as changed on the first poll
+        for file in self.files_of(path) {
+            if let Some(modified) = modified_time(&file) {
+                self.modified.insert(file, modified);
+            }
+        }
+    }
+
+    /// Returns files that were created or modified since the last poll
+    pub fn poll(&mut self, delta_time: &f32) -> Vec<String> {
+        let mut changed: Vec<String> = Vec::new();
+        if !self.is_enabled {
+            return changed;
+        }
+        self.timer += delta_time;
+        if self.timer < Self::POLL_INTERVAL {
+            return changed;
+        }
+        self.timer = 0f32;
+
+        let files: Vec<String> = self
+            .watched
+            .iter()
+            .flat_map(|path| self.files_of(path))
+            .collect();
+        for file in files {
+            let modified: SystemTime = match modified_time(&file) {
+                Some(modified) => modified,
+                None => continue,
+            };
+            if self.modified.get(&file) != Some(&modified) {
+                self.modified.insert(file.clone(), modified);
+                changed.push(file);
+            }
+        }
+        changed
+    }
+
+    /// Records result of a reload, successful reload clears the previous error of the file
+    pub fn report(&mut self, path: &str, result: Result<(), String>) {
+        self.errors.retain(|(error_path, _)| error_path != path);
+        match result {
+            Ok(()) => log_info!("Reloaded {}", path),
+            Err(error) => {
+                log_error!("Reload of {} failed: {}", path, error);
+                self.errors.push((path.to_string(), error));
+            }
+        }
+    }
+
+    /// Draws reload errors over the whole window
+    pub fn draw(&self, d: &mut RaylibDrawHandle, font: &Font) {
+        if self.errors.is_empty() {
+            return;
+        }
+
+        let width: f32 = d.get_screen_width() as f32;
+        let style: TextStyle = TextStyle::new(Self::FONT_SIZE, Color::WHITE)
+            .line_height(Self::LINE_GAP)
+            .wrap(width - Self::MARGIN * 2f32);
+        let text: String = self
+            .errors
+            .iter()
+            .map(|(_, error)| error.as_str())
+            .collect::<Vec<&str>>()
+            .join("\n");
+        let text_height: f32 = text_size(font, &text, &style).y;
+        d.draw_rectangle_v(
+            Vector2::new(0f32, 0f32),
+            Vector2::new(width, Self::MARGIN * 2f32 + Self::LINE_GAP + text_height),
+            Color::BLACK.alpha(0.8f32),
+        );
+        draw_text(
+            d,
+            font,
+            "Reload failed:",
+            Vector2::new(Self::MARGIN, Self::MARGIN),
+            &TextStyle::new(Self::FONT_SIZE, Color::RED),
+        );
+        draw_text(
+            d,
+            font,
+            &text,
+            Vector2::new(Self::MARGIN, Self::MARGIN + Self::LINE_GAP),
+            &style,
+        );
+    }
+
+    fn files_of(&self, path: &str) -> Vec<String> {
+        match fs::read_dir(path) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|entry| entry.is_file())
+                .map(|entry| entry.to_string_lossy().to_string())
+                .collect(),
+            // Not a directory, watched as a single file
+            Err(_) => vec![path.to_string()],
+        }
+    }
+}
+
+/// Developer tools are only available in developer mode
+pub fn is_dev_mode() -> bool {
+    env::var(DEV_MODE_ENV).is_ok_and(|value| value != "0")
+}
+
+fn modified_time(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
@@ -0,0 +1,284 @@
+use raylib::prelude::*;
+
+use crate::global::*;
+use crate::locale::*;
+use crate::run::*;
+use crate::text::*;
+
+/// Heads-up display drawn over the playfield in render target space,
+/// window scaling is left to draw_on_target
+pub struct Hud {
+    displayed_score: f64,
+}
+
+impl Hud {
+    const SCORE_DIGITS: usize = 10usize;
+    // Share of the remaining difference rolled per second
+    const SCORE_ROLL_SPEED: f64 = 8f64;
+    const MARGIN: f32 = 16f32;
+    const FONT_SIZE: f32 = 36f32;
+    const LINE_GAP: f32 = 38f32;
+    const ICON_SIZE: f32 = 11f32;
+    const ICON_GAP: f32 = 22f32;
+    const BAR_ALPHA: f32 = 0.55f32;
+    const LABEL_WHITE: Color = Color::new(255u8, 255u8, 255u8, 191u8);
+
+    pub fn new() -> Self {
+        Self {
+            displayed_score: 0f64,
+        }
+    }
+
+    /// Rolls displayed score towards the real one
+    pub fn update(&mut self, delta_time: &f32, run: &RunData) {
+        let target: f64 = run.score as f64;
+        if self.displayed_score > target {
+            // Score went down (continue, retry), no rolling back
+            self.displayed_score = target;
+        } else if self.displayed_score < target {
+            let step: f64 = ((target - self.displayed_score)
+                * (Self::SCORE_ROLL_SPEED * *delta_time as f64).min(1f64))
+            .max(1f64);
+            self.displayed_score = (self.displayed_score + step).min(target);
+        }
+    }
+
+    pub fn draw(
+        &self,
+        d: &mut impl RaylibDraw,
+        font: &Font,
+        locale: &Locale,
+        run: &RunData,
+        hiscore: u64,
+    ) {
+        let width: f32 = SCREEN_WIDTH as f32;
+        let height: f32 = SCREEN_HEIGHT as f32;
+        let top_bar_height: f32 = Self::MARGIN * 2f32 + Self::LINE_GAP * 2f32;
+        let bottom_bar_height: f32 = Self::MARGIN * 2f32 + Self::LINE_GAP * 2f32;
+        let bar_color: Color = Color::BLACK.alpha(Self::BAR_ALPHA);
+
+        // TOP BAR: scores on the left, lives and bombs on the right
+        {
+            d.draw_rectangle_v(
+                Vector2::new(0f32, 0f32),
+                Vector2::new(width, top_bar_height),
+                bar_color,
+            );
+
+            let left_x: f32 = Self::MARGIN;
+            let right_x: f32 = width / 2f32 + Self::MARGIN;
+            let shown_score: u64 = self.displayed_score as u64;
+
+            self.draw_field(
+                d,
+                font,
+                locale.tr("hud.hiscore"),
+                &Self::format_score(hiscore.max(shown_score)),
+                Vector2::new(left_x, Self::MARGIN),
+            );
+            self.draw_field(
+                d,
+                font,
+                locale.tr("hud.score"),
+                &Self::format_score(shown_score),
+                Vector2::new(left_x, Self::MARGIN + Self::LINE_GAP),
+            );
+
+            self.draw_icon_row(
+                d,
+                font,
+                locale.tr("hud.player"),
+                run.lives,
+                run.life_pieces,
+                LIFE_PIECES_PER_LIFE,
+                Color::RED,
+                Vector2::new(right_x, Self::MARGIN),
+            );
+            self.draw_icon_row(
+                d,
+                font,
+                locale.tr("hud.bomb"),
+                run.bombs,
+                run.bomb_pieces,
+                BOMB_PIECES_PER_BOMB,
+                Color::GREEN,
+                Vector2::new(right_x, Self::MARGIN + Self::LINE_GAP),
+            );
+        }
+
+        // BOTTOM BAR: power, graze and point item value
+        {
+            let bar_y: f32 = height - bottom_bar_height;
+            d.draw_rectangle_v(
+                Vector2::new(0f32, bar_y),
+                Vector2::new(width, bottom_bar_height),
+                bar_color,
+            );
+
+            let column_width: f32 = (width - Self::MARGIN * 2f32) / 2f32;
+            self.draw_field(
+                d,
+                font,
+                locale.tr("hud.power"),
+                &format!("{:.2} / {:.2}", run.power, MAX_POWER),
+                Vector2::new(Self::MARGIN, bar_y + Self::MARGIN),
+            );
+            self.draw_field(
+                d,
+                font,
+                locale.tr("hud.graze"),
+                &format!("{}", run.graze),
+                Vector2::new(Self::MARGIN, bar_y + Self::MARGIN + Self::LINE_GAP),
+            );
+            self.draw_field(
+                d,
+                font,
+                locale.tr("hud.point"),
+                &format!("{}", run.point_value),
+                Vector2::new(Self::MARGIN + column_width, bar_y + Self::MARGIN),
+            );
+            self.draw_field(
+                d,
+                font,
+                locale.tr("hud.stage"),
+                &format!("{} {}", locale.tr(run.difficulty.text_key()), run.stage),
+                Vector2::new(
+                    Self::MARGIN + column_width,
+                    bar_y + Self::MARGIN + Self::LINE_GAP,
+                ),
+            );
+        }
+
+        // BOSS: health bar under the top bar, spell card name and timer
+        if let Some(boss) = &run.boss {
+            const HEALTH_BAR_HEIGHT: f32 = 8f32;
+            const TIMER_WIDTH: f32 = 72f32;
+
+            let bar_y: f32 = top_bar_height + Self::MARGIN / 2f32;
+            let bar_width: f32 = width - Self::MARGIN * 3f32 - TIMER_WIDTH;
+            d.draw_rectangle_v(
+                Vector2::new(Self::MARGIN, bar_y),
+                Vector2::new(bar_width, HEALTH_BAR_HEIGHT),
+                bar_color,
+            );
+            d.draw_rectangle_v(
+                Vector2::new(Self::MARGIN, bar_y),
+                Vector2::new(bar_width * boss.health.clamp(0f32, 1f32), HEALTH_BAR_HEIGHT),
+                Color::WHITE,
+            );
+
+            // Timer turns red on the last seconds
+            let timer_text: String = format!("{:05.2}", boss.spell_timer.max(0f32));
+            draw_text(
+                d,
+                font,
+                &timer_text,
+                Vector2::new(
+                    width - Self::MARGIN - TIMER_WIDTH,
+                    bar_y - Self::FONT_SIZE / 3f32,
+                ),
+                &Self::text_style(if boss.spell_timer < 10f32 {
+                    Color::RED
+                } else {
+                    Color::WHITE
+                }),
+            );
+
+            if let Some(spell_name) = &boss.spell_name {
+                draw_text(
+                    d,
+                    font,
+                    spell_name,
+                    Vector2::new(
+                        width - Self::MARGIN,
+                        bar_y + HEALTH_BAR_HEIGHT + Self::MARGIN / 2f32,
+                    ),
+                    &Self::text_style(Color::WHITE).align(Align::Right),
+                );
+            }
+        }
+    }
+
+    /// Label with its value on the right
+    fn draw_field(
+        &self,
+        d: &mut impl RaylibDraw,
+        font: &Font,
+        label: &str,
+        value: &str,
+        position: Vector2,
+    ) {
+        const VALUE_OFFSET: f32 = 120f32;
+        draw_text(
+            d,
+            font,
+            label,
+            position,
+            &Self::text_style(Self::LABEL_WHITE),
+        );
+        draw_text(
+            d,
+            font,
+            value,
+            Vector2::new(position.x + VALUE_OFFSET, position.y),
+            &Self::text_style(Color::WHITE),
+        );
+    }
+
+    /// Row of icons for whole lives/bombs plus a counter of collected pieces
+    #[allow(clippy::too_many_arguments)]
+    fn draw_icon_row(
+        &self,
+        d: &mut impl RaylibDraw,
+        font: &Font,
+        label: &str,
+        count: u8,
+        pieces: u8,
+        pieces_per_one: u8,
+        color: Color,
+        position: Vector2,
+    ) {
+        const ICONS_OFFSET: f32 = 100f32;
+        const MAX_ICONS: u8 = 7u8;
+
+        draw_text(
+            d,
+            font,
+            label,
+            position,
+            &Self::text_style(Self::LABEL_WHITE),
+        );
+        let icons_y: f32 = position.y + Self::FONT_SIZE / 2f32;
+        for i in 0..count.min(MAX_ICONS) {
+            d.draw_poly(
+                Vector2::new(
+                    position.x + ICONS_OFFSET + Self::ICON_GAP * i as f32,
+                    icons_y,
+                ),
+                5i32,
+                Self::ICON_SIZE,
+                -90f32,
+                color,
+            );
+        }
+        draw_text(
+            d,
+            font,
+            &format!("{}/{}", pieces, pieces_per_one),
+            Vector2::new(
+                position.x + ICONS_OFFSET + Self::ICON_GAP * MAX_ICONS as f32,
+                position.y,
+            ),
+            &Self::text_style(Self::LABEL_WHITE),
+        );
+    }
+
+    /// Every HUD label is shadowed to stay readable over the background
+    const fn text_style(color: Color) -> TextStyle {
+        TextStyle::new(Self::FONT_SIZE, color).shadow(2f32, Color::BLACK)
+    }
+
+    fn format_score(score: u64) -> String {
+        format!("{:0width$}", score, width = Self::SCORE_DIGITS)
+    }
+}
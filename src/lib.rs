@@ -0,0 +1,40 @@
+//! Game modules, shared by the game and the tools of src/bin
+
+// Game objects are made with new(), they have no meaningful default
+#![allow(clippy::new_without_default)]
+
+// First, so every module below has its macros
+#[macro_use]
+pub mod log;
+
+pub mod archive;
+pub mod assets;
+pub mod background;
+pub mod cli;
+pub mod cmap;
+pub mod console;
+pub mod crash;
+#[cfg(feature = "debug_overlay")]
+pub mod debug_overlay;
+pub mod end_screen;
+pub mod game_over;
+pub mod global;
+pub mod greet_screen;
+pub mod hot_reload;
+pub mod hud;
+pub mod locale;
+pub mod main_menu;
+pub mod menu;
+pub mod player;
+pub mod replay;
+pub mod rng;
+pub mod run;
+pub mod save_data;
+pub mod score;
+pub mod screenshot;
+pub mod shaders;
+pub mod simulation;
+pub mod sprite;
+pub mod stage_script;
+pub mod text;
+pub mod user_dirs;
@@ -0,0 +1,173 @@
+use raylib::prelude::*;
+use std::collections::HashMap;
+
+use crate::archive::*;
+use crate::assets::*;
+
+pub const LANG_DIR_PATH: &str = "lang";
+pub const DEFAULT_LANGUAGE: &str = "en";
+const LANGUAGES_FILE_PATH: &str = "lang/languages.txt";
+
+/// UI strings of the chosen language, referenced by key.
+/// Keys missing in the language are taken from English, keys missing in English are shown as is
+pub struct Locale {
+    code: String,
+    strings: HashMap<String, String>,
+    fallback: HashMap<String, String>,
+    // Code and native name of every language from languages.txt
+    languages: Vec<(String, String)>,
+    font_id: &'static str,
+}
+
+impl Locale {
+    /// Loads string table of the language, unknown code falls back to English
+    pub fn load(code: &str) -> Self {
+        let languages: Vec<(String, String)> = load_languages();
+        let code: String = if languages.iter().any(|(known, _)| known == code) {
+            code.to_string()
+        } else {
+            log_warn!(
+                "Language '{}' is not in {}, using '{}'",
+                code,
+                LANGUAGES_FILE_PATH,
+                DEFAULT_LANGUAGE
+            );
+            DEFAULT_LANGUAGE.to_string()
+        };
+
+        let fallback: HashMap<String, String> = load_strings(DEFAULT_LANGUAGE);
+        let strings: HashMap<String, String> = if code == DEFAULT_LANGUAGE {
+            HashMap::new()
+        } else {
+            load_strings(&code)
+        };
+        let missing: usize = fallback
+            .keys()
+            .filter(|key| !strings.contains_key(*key))
+            .count();
+        if code != DEFAULT_LANGUAGE && missing > 0 {
+            log_warn!(
+                "{}: {} strings are missing, English is used for them",
+                lang_file_path(&code),
+                missing
+            );
+        }
+
+        Self {
+            code,
+            strings,
+            fallback,
+            languages,
+            font_id: MAIN_FONT_ID,
+        }
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// String by its key
+    pub fn tr<'a>(&'a self, key: &'a str) -> &'a str {
+        self.strings
+            .get(key)
+            .or_else(|| self.fallback.get(key))
+            .map(|text| text.as_str())
+            .unwrap_or(key)
+    }
+
+    /// String by its key with every "{}" replaced by the next argument
+    pub fn tr_format(&self, key: &str, args: &[&str]) -> String {
+        let mut parts = self.tr(key).split("{}");
+        let mut text: String = parts.next().unwrap_or("").to_string();
+        for (i, part) in parts.enumerate() {
+            text.push_str(args.get(i).copied().unwrap_or(""));
+            text.push_str(part);
+        }
+        text
+    }
+
+    /// Code and native name of every language
+    pub fn languages(&self) -> &[(String, String)] {
+        &self.languages
+    }
+
+    /// Id of the font that has glyphs for this language
+    pub fn font_id(&self) -> &'static str {
+        self.font_id
+    }
+
+    /// Loads main font with glyphs of this language, secondary font is used if main
+    /// one doesn't cover them
+    pub fn load_font(&mut self, thread: &RaylibThread, assets: &mut AssetManager) {
+        let glyphs: String = self
+            .strings
+            .values()
+            .chain(self.fallback.values())
+            .flat_map(|text| text.chars())
+            .collect();
+        self.font_id = if assets.load_font(thread, MAIN_FONT_ID, &glyphs) {
+            MAIN_FONT_ID
+        } else {
+            log_info!(
+                "Font '{}' is used for language '{}'",
+                FALLBACK_FONT_ID,
+                self.code
+            );
+            assets.load_font(thread, FALLBACK_FONT_ID, &glyphs);
+            FALLBACK_FONT_ID
+        };
+    }
+}
+
+fn lang_file_path(code: &str) -> String {
+    format!("{}/{}.txt", LANG_DIR_PATH, code)
+}
+
+/// One language per line: code and native name, "#" starts a comment line
+fn load_languages() -> Vec<(String, String)> {
+    let data: String = match read_file_string(LANGUAGES_FILE_PATH) {
+        Some(data) => data,
+        None => {
+            log_warn!(
+                "{} is missing, only English is available",
+                LANGUAGES_FILE_PATH
+            );
+            return vec![(DEFAULT_LANGUAGE.to_string(), "English".to_string())];
+        }
+    };
+    data.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let (code, name) = line.split_once(char::is_whitespace)?;
+            Some((code.to_string(), name.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Line format: key = value, "#" starts a comment line
+fn load_strings(code: &str) -> HashMap<String, String> {
+    let path: String = lang_file_path(code);
+    let mut strings: HashMap<String, String> = HashMap::new();
+    let data: String = match read_file_string(&path) {
+        Some(data) => data,
+        None => {
+            log_warn!("{} is missing", path);
+            return strings;
+        }
+    };
+
+    for (line_number, line) in data.lines().enumerate() {
+        let line: &str = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.split_once('=') {
+            Some((key, value)) => {
+                strings.insert(key.trim().to_string(), value.trim().to_string());
+            }
+            None => log_warn!("{}:{}: expected 'key = value'", path, line_number + 1),
+        }
+    }
+    strings
+}
@@ -0,0 +1,313 @@
+use raylib::prelude::TraceLogLevel;
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const LOG_FILE_NAME: &str = "nmf.log";
+const MAX_LOG_FILE_SIZE: u64 = 1024u64 * 1024u64;
+// Rotated files are nmf.1.log, nmf.2.log..., bigger number is older
+const MAX_OLD_LOG_FILES: u32 = 3u32;
+// Lines kept in memory for crash reports
+const MAX_RECENT_LINES: usize = 200usize;
+
+// MACROS, module of the message is where the macro is called
+#[macro_export]
+macro_rules! log_error {
+    ($($arg:tt)*) => {
+        $crate::log::write_log($crate::log::LogLevel::Error, module_path!(), format_args!($($arg)*))
+    };
+}
+
+#[macro_export]
+macro_rules! log_warn {
+    ($($arg:tt)*) => {
+        $crate::log::write_log($crate::log::LogLevel::Warn, module_path!(), format_args!($($arg)*))
+    };
+}
+
+#[macro_export]
+macro_rules! log_info {
+    ($($arg:tt)*) => {
+        $crate::log::write_log($crate::log::LogLevel::Info, module_path!(), format_args!($($arg)*))
+    };
+}
+
+#[macro_export]
+macro_rules! log_debug {
+    ($($arg:tt)*) => {
+        $crate::log::write_log($crate::log::LogLevel::Debug, module_path!(), format_args!($($arg)*))
+    };
+}
+
+#[macro_export]
+macro_rules! log_trace {
+    ($($arg:tt)*) => {
+        $crate::log::write_log($crate::log::LogLevel::Trace, module_path!(), format_args!($($arg)*))
+    };
+}
+
+#[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Off => "OFF",
+            LogLevel::Error => "ERROR",
+            LogLevel::Warn => "WARN",
+            LogLevel::Info => "INFO",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Trace => "TRACE",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "off" | "none" => Some(LogLevel::Off),
+            "error" => Some(LogLevel::Error),
+            "warn" | "warning" => Some(LogLevel::Warn),
+            "info" => Some(LogLevel::Info),
+            "debug" => Some(LogLevel::Debug),
+            "trace" => Some(LogLevel::Trace),
+            _ => None,
+        }
+    }
+
+    /// Level of raylib's own messages, they go to stdout by raylib
+    pub fn raylib_level(&self) -> TraceLogLevel {
+        match self {
+            LogLevel::Off => TraceLogLevel::LOG_NONE,
+            LogLevel::Error => TraceLogLevel::LOG_ERROR,
+            LogLevel::Warn => TraceLogLevel::LOG_WARNING,
+            LogLevel::Info => TraceLogLevel::LOG_INFO,
+            LogLevel::Debug => TraceLogLevel::LOG_DEBUG,
+            LogLevel::Trace => TraceLogLevel::LOG_TRACE,
+        }
+    }
+}
+
+/// Default level with levels of single modules, "info,assets=debug,main_menu=trace".
+/// Module level covers its submodules, the longest matching module wins
+#[derive(Clone)]
+pub struct LogFilter {
+    level: LogLevel,
+    modules: Vec<(String, LogLevel)>,
+}
+
+impl LogFilter {
+    pub fn new(level: LogLevel) -> Self {
+        Self {
+            level,
+            modules: Vec::new(),
+        }
+    }
+
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut filter: LogFilter = Self::new(LogLevel::Info);
+        for part in spec.split(',').map(|part| part.trim()) {
+            let level_name: &str = match part.split_once('=') {
+                Some((_, level_name)) => level_name,
+                None => part,
+            };
+            let level: LogLevel = LogLevel::from_name(level_name)
+                .ok_or_else(|| format!("Unknown log level '{}'", level_name))?;
+            match part.split_once('=') {
+                Some((module, _)) => filter.modules.push((module.to_string(), level)),
+                None => filter.level = level,
+            }
+        }
+        Ok(filter)
+    }
+
+    /// Level of modules without their own
+    pub fn level(&self) -> LogLevel {
+        self.level
+    }
+
+    fn level_of(&self, module: &str) -> LogLevel {
+        self.modules
+            .iter()
+            .filter(|(name, _)| {
+                module == name
+                    || module
+                        .strip_prefix(name.as_str())
+                        .is_some_and(|rest| rest.starts_with("::"))
+            })
+            .max_by_key(|(name, _)| name.len())
+            .map(|(_, level)| *level)
+            .unwrap_or(self.level)
+    }
+}
+
+struct Logger {
+    filter: LogFilter,
+    file_path: Option<String>,
+    file: Option<File>,
+    file_size: u64,
+}
+
+impl Logger {
+    fn write_file(&mut self, line: &str) {
+        let file_path: &str = match &self.file_path {
+            Some(file_path) => file_path,
+            None => return,
+        };
+        if self.file_size + line.len() as u64 > MAX_LOG_FILE_SIZE {
+            self.file = None;
+            rotate_log_files(file_path);
+            self.file = File::create(file_path).ok();
+            self.file_size = 0u64;
+        }
+        if let Some(file) = self.file.as_mut() {
+            if writeln!(file, "{}", line).is_ok() {
+                self.file_size += line.len() as u64 + 1u64;
+            }
+        }
+    }
+}
+
+static LOGGER: Mutex<Option<Logger>> = Mutex::new(None);
+static FRAME: AtomicU64 = AtomicU64::new(0u64);
+static RECENT_LINES: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Starts logging to stderr and the file, last session's file is rotated away.
+/// Until it is called messages of Info and above go to stderr only
+pub fn init_log(filter: LogFilter, file_path: Option<String>) {
+    let file: Option<File> = file_path.as_deref().and_then(create_log_file);
+    *LOGGER.lock().unwrap_or_else(|err| err.into_inner()) = Some(Logger {
+        filter,
+        file_path,
+        file,
+        file_size: 0u64,
+    });
+}
+
+/// Starts writing to the file after init_log, messages written before go into it first.
+/// For a file whose path is found only once logging works
+pub fn set_log_file(file_path: String) {
+    let mut file: Option<File> = create_log_file(&file_path);
+    let mut file_size: u64 = 0u64;
+    if let Some(file) = file.as_mut() {
+        for line in recent_log_lines() {
+            if writeln!(file, "{}", line).is_ok() {
+                file_size += line.len() as u64 + 1u64;
+            }
+        }
+    }
+    if let Some(logger) = LOGGER
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .as_mut()
+    {
+        logger.file_path = Some(file_path);
+        logger.file = file;
+        logger.file_size = file_size;
+    }
+}
+
+/// Rotates last session's file away and creates the new one
+fn create_log_file(file_path: &str) -> Option<File> {
+    rotate_log_files(file_path);
+    match File::create(file_path) {
+        Ok(file) => Some(file),
+        Err(err) => {
+            eprintln!("{}: {}", file_path, err);
+            None
+        }
+    }
+}
+
+/// Frame number that goes into every message
+pub fn set_log_frame(frame: u64) {
+    FRAME.store(frame, Ordering::Relaxed);
+}
+
+/// Writes a message if the level of its module allows it, used by the log macros
+pub fn write_log(level: LogLevel, module_path: &str, args: fmt::Arguments) {
+    // "nmf::assets" -> "assets", crate root is "main"
+    let module: &str = module_path
+        .split_once("::")
+        .map(|(_, module)| module)
+        .unwrap_or("main");
+    let mut logger = LOGGER.lock().unwrap_or_else(|err| err.into_inner());
+    let max_level: LogLevel = match logger.as_ref() {
+        Some(logger) => logger.filter.level_of(module),
+        None => LogLevel::Info,
+    };
+    if level == LogLevel::Off || level > max_level {
+        return;
+    }
+
+    let line: String = format!(
+        "{} #{} {:<5} {}: {}",
+        timestamp(),
+        FRAME.load(Ordering::Relaxed),
+        level.as_str(),
+        module,
+        args
+    );
+    eprintln!("{}", line);
+    if let Some(logger) = logger.as_mut() {
+        logger.write_file(&line);
+    }
+    let mut recent_lines = RECENT_LINES.lock().unwrap_or_else(|err| err.into_inner());
+    if recent_lines.len() >= MAX_RECENT_LINES {
+        recent_lines.pop_front();
+    }
+    recent_lines.push_back(line);
+}
+
+/// Last written messages, oldest first. Empty if a panic happened while they were changed
+pub fn recent_log_lines() -> Vec<String> {
+    match RECENT_LINES.try_lock() {
+        Ok(recent_lines) => recent_lines.iter().cloned().collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// "HH:MM:SS.mmm" of current UTC time
+fn timestamp() -> String {
+    let millis: u128 = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_millis())
+        .unwrap_or(0u128);
+    let seconds: u128 = millis / 1000u128 % 86400u128;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        seconds / 3600u128,
+        seconds % 3600u128 / 60u128,
+        seconds % 60u128,
+        millis % 1000u128
+    )
+}
+
+/// nmf.log -> nmf.1.log -> nmf.2.log..., the oldest one is removed
+fn rotate_log_files(file_path: &str) {
+    fs::remove_file(old_log_path(file_path, MAX_OLD_LOG_FILES)).ok();
+    for index in (1u32..MAX_OLD_LOG_FILES).rev() {
+        fs::rename(
+            old_log_path(file_path, index),
+            old_log_path(file_path, index + 1u32),
+        )
+        .ok();
+    }
+    fs::rename(file_path, old_log_path(file_path, 1u32)).ok();
+}
+
+fn old_log_path(file_path: &str, index: u32) -> String {
+    match file_path.strip_suffix(".log") {
+        Some(stem) => format!("{}.{}.log", stem, index),
+        None => format!("{}.{}", file_path, index),
+    }
+}
@@ -1,14 +1,75 @@
 use raylib::prelude::*;
+use std::env;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::process;
 
-mod global;
-mod greet_screen;
-mod main_menu;
+#[macro_use]
+extern crate nmf;
 
-use crate::global::*;
-use crate::greet_screen::*;
-use crate::main_menu::*;
+use nmf::assets::*;
+use nmf::background::*;
+use nmf::cli::*;
+use nmf::console::*;
+use nmf::crash::*;
+#[cfg(feature = "debug_overlay")]
+use nmf::debug_overlay::*;
+use nmf::end_screen::*;
+use nmf::game_over::*;
+use nmf::global::*;
+use nmf::greet_screen::*;
+use nmf::hot_reload::*;
+use nmf::hud::*;
+use nmf::locale::*;
+use nmf::log::*;
+use nmf::main_menu::*;
+use nmf::player::*;
+use nmf::replay::*;
+use nmf::run::*;
+use nmf::save_data::*;
+use nmf::score::*;
+use nmf::screenshot::*;
+use nmf::shaders::*;
+use nmf::simulation::*;
+use nmf::sprite::*;
+use nmf::stage_script::*;
+use nmf::text::*;
+use nmf::user_dirs::*;
 
 fn main() {
+    // COMMAND LINE
+    let launch: LaunchOptions = match LaunchOptions::parse(env::args().skip(1)) {
+        Ok(launch) => launch,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, help_text());
+            process::exit(2);
+        }
+    };
+    if launch.show_help {
+        print!("{}", help_text());
+        return;
+    }
+    if let Some(data_dir) = &launch.data_dir {
+        if let Err(err) = env::set_current_dir(data_dir) {
+            eprintln!("{}: {}", data_dir, err);
+            process::exit(1);
+        }
+    }
+    // Finding the data directory logs too, so the filter goes in before the file
+    init_log(launch.log_filter.clone(), None);
+    set_log_file(data_path(LOG_FILE_NAME));
+    install_crash_handler();
+    // USER FILES that older versions kept in the working directory. Options file that
+    // only holds defaults came with the game, so there is nothing to carry over
+    if !fs::read_to_string(OPTIONS_FILE_NAME)
+        .is_ok_and(|data| Options::is_default_file(OPTIONS_FILE_NAME, &data))
+    {
+        migrate_legacy_file(OPTIONS_FILE_NAME, &config_path(OPTIONS_FILE_NAME));
+    }
+    migrate_legacy_file(SAVE_FILE_NAME, &data_path(SAVE_FILE_NAME));
+    migrate_legacy_file(SCORES_FILE_NAME, &data_path(SCORES_FILE_NAME));
+
     // STATE MANAGER
     let mut game_state = GameState::GreetingScreen;
 
@@ -17,31 +78,26 @@ fn main() {
         .size(SCREEN_WIDTH, SCREEN_HEIGHT)
         .resizable()
         .title("Noster: Mare Frigoris")
+        .log_level(launch.log_filter.level().raylib_level())
         .build();
 
     // Minimum window size
     rl.set_window_min_size(240i32, 320i32);
 
-    // Initial window size
-    rl.set_window_size(320, 320);
-    rl.set_window_position(
-        rl.get_screen_width() / 2 - 160,
-        rl.get_screen_height() / 2 - 160,
-    );
-
-    // INIT GAME DATA
+    // INIT GAME DATA, window mode, size and position come from options
     let mut gd: GameData = GameData::new();
+    if let Some(config_path) = &launch.config_path {
+        gd.set_config_path(config_path);
+    }
     gd.load_config(&mut rl);
+    if let Some(window_mode) = launch.window_mode {
+        gd.set_window_mode(&mut rl, window_mode);
+    }
     // TODO: make a gd.init function, that will do everything needed on init
 
     // Setting max fps
     rl.set_target_fps(gd.get_max_fps());
 
-    // LOAD FONT FROM MEMORY
-    let font: Font = rl
-        .load_font_from_memory(&thread, ".ttf", MAIN_FONT, 84i32, None)
-        .unwrap();
-
     // INIT CAMERA
     let mut cam = Camera3D::perspective(
         Vector3::new(0f32, 10f32, 10f32),
@@ -55,6 +111,9 @@ fn main() {
         .load_render_texture(&thread, SCREEN_WIDTH as u32, SCREEN_HEIGHT as u32)
         .unwrap();
 
+    let mut scale_filter: ScaleFilter = gd.scale_filter();
+    render_target.set_texture_filter(&thread, scale_filter.texture_filter());
+
     // INIT AUDIO DEVICE
     //  FIXME:
     //      [x] move to global
@@ -66,98 +125,439 @@ fn main() {
     //Music::set_volume(&mut self, volume);
     //Sound::set_volume(&mut self, volume);
 
+    // LOAD ASSETS
+    let mut assets: AssetManager = AssetManager::new(&mut rl, &thread);
+    assets.load_music(&audio, MENU_BGM_ID);
+    let mut tracks: TrackList = TrackList::load();
+
+    // LOAD LANGUAGE, picks the font that has its glyphs
+    let mut locale: Locale = Locale::load(gd.language());
+    locale.load_font(&thread, &mut assets);
+    if gd.language() != locale.code() {
+        // Unknown language fell back to English, options follow it
+        gd.set_language(locale.code());
+    }
+
+    // LOAD SHADERS
+    let mut shaders: ShaderManager = ShaderManager::new();
+    shaders.load(&mut rl, &thread, &assets, FOG_SHADER);
+
+    // LOAD SPRITES
+    let mut player_atlas: Option<SpriteAtlas> =
+        SpriteAtlas::load(&mut rl, &thread, &mut assets, PLAYER_ATLAS);
+
     // INIT GREETING SCREEN
     let mut greet_screen: GreetScreen = GreetScreen::new();
 
     // INIT MAIN MENU
     let mut main_menu: MainMenu = MainMenu::new();
 
-    // MainMenuMusic
-    let main_menu_bgm: Music = audio.new_music("bgm/nmf_01.wav").unwrap();
+    // INIT HUD
+    let mut hud: Hud = Hud::new();
+
+    // INIT GAME OVER SCREEN
+    let mut game_over_screen: GameOverScreen = GameOverScreen::new();
+
+    // INIT END SCREEN
+    let mut end_screen: EndScreen = EndScreen::new();
+
+    // RUN, SCORES AND SAVE DATA
+    let mut run: RunData = RunData::new(Difficulty::Normal);
+    let mut scores: HighScores = HighScores::load();
+    let mut save_data: SaveData = SaveData::load();
+    let mut stage_script: StageScript = StageScript::load(run.stage);
+    let mut background: Background = Background::new();
+    let mut player: Player = Player::new();
+    let mut fixed_step: FixedStep = FixedStep::new();
+
+    // LAUNCH OVERRIDES from command line
+    let mut replay_player: Option<ReplayPlayer> = None;
+    if launch.skip_intro {
+        game_state = GameState::MainMenu;
+    }
+    if launch.stage.is_some() || launch.difficulty.is_some() {
+        let difficulty: Difficulty = launch.difficulty.unwrap_or(Difficulty::Normal);
+        run = match launch.stage {
+            Some(stage) => RunData::with_seed(difficulty, stage, rand::random()),
+            None => RunData::new(difficulty),
+        };
+        game_state = GameState::Playing;
+    }
+    if let Some(replay_path) = &launch.replay_path {
+        match Replay::load(replay_path) {
+            Ok(replay) => {
+                let player: ReplayPlayer = ReplayPlayer::new(replay);
+                run = player.run();
+                replay_player = Some(player);
+                game_state = GameState::Playing;
+            }
+            Err(err) => log_error!("{}", err),
+        }
+    }
+
+    // SCREENSHOTS
+    let mut screenshots: Screenshots = Screenshots::new();
 
-    while !rl.window_should_close() && !gd.window_should_close() {
-        // PRE-UPDATE, GLOBAL KEYBOARD INPUT, ETC. | Probably will not be needed
-        let delta_time: f32 = rl.get_frame_time();
+    // DEBUG OVERLAY, only in builds with the feature
+    #[cfg(feature = "debug_overlay")]
+    let mut debug_overlay: DebugOverlay = DebugOverlay::new();
 
-        // UPDATE
-        match game_state {
-            // include upd + draw to each state since they have different logic
-            // and drawing tasks
-            GameState::GreetingScreen => {
-                greet_screen.update(&rl, &delta_time, &mut cam, &mut game_state);
+    // DEVELOPER MODE
+    let mut hot_reload: HotReload = HotReload::new();
+    hot_reload.watch("shaders");
+    hot_reload.watch(STAGES_DIR_PATH);
+    hot_reload.watch(TRACKS_FILE_PATH);
+    hot_reload.watch(gd.config_path());
+    hot_reload.watch(LANG_DIR_PATH);
+    let mut console: Console = Console::new();
+    console.register_all(stage_commands());
+    let mut dev_cheats: DevCheats = DevCheats::new();
+
+    // LOGGING, messages carry the frame number
+    let mut frame: u64 = 0u64;
+    let mut logged_game_state: GameState = game_state;
+    log_info!("Starting in {:?}", game_state);
+    set_crash_context(game_state, &gd);
+
+    // A panic in the loop is caught, so the replay of the run is kept for the crash report
+    let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+        while !rl.window_should_close() && !gd.window_should_close() {
+            frame += 1u64;
+            set_log_frame(frame);
+            // PRE-UPDATE, GLOBAL KEYBOARD INPUT, ETC. | Probably will not be needed
+            let frame_time: f32 = rl.get_frame_time();
+            // Game speed of the console scales screens and gameplay, developer tools,
+            // screenshots and the console itself take frame_time and keep real time
+            let delta_time: f32 = frame_time * dev_cheats.game_speed;
+            gd.remember_window(&rl);
+            #[cfg(feature = "debug_overlay")]
+            debug_overlay.update(&rl, &frame_time);
+
+            // HOT RELOAD, changed files are reloaded in place
+            for path in hot_reload.poll(&frame_time) {
+                if path.ends_with(".vs") || path.ends_with(".fs") {
+                    // Shaders are loaded by manifest id, the path without extension leads to it
+                    let shader_path: PathBuf = Path::new(&path).with_extension("");
+                    let ids: Vec<String> = assets.ids_of_path(AssetKind::Shader, &shader_path);
+                    let failed: Vec<&String> = ids
+                        .iter()
+                        .filter(|id| !shaders.load(&mut rl, &thread, &assets, id))
+                        .collect();
+                    let result: Result<(), String> = if ids.is_empty() {
+                        Err(format!("{} is not a shader of the asset manifest", path))
+                    } else if failed.is_empty() {
+                        Ok(())
+                    } else {
+                        Err(format!("Shader {:?} did not load, see log", failed))
+                    };
+                    hot_reload.report(&path, result);
+                } else if path == stage_file_path(run.stage) {
+                    // Segment under the camera starts over with the new script
+                    stage_script = StageScript::load(run.stage);
+                    background.restart_segment(&stage_script);
+                    let result: Result<(), String> = if stage_script.errors.is_empty() {
+                        Ok(())
+                    } else {
+                        Err(stage_script.errors.join("\n"))
+                    };
+                    hot_reload.report(&path, result);
+                } else if path == TRACKS_FILE_PATH {
+                    tracks = TrackList::load();
+                    let result: Result<(), String> = if tracks.errors.is_empty() {
+                        Ok(())
+                    } else {
+                        Err(tracks.errors.join("\n"))
+                    };
+                    hot_reload.report(&path, result);
+                } else if path == gd.config_path() {
+                    let result: Result<(), String> = gd.reload_config(&mut rl);
+                    hot_reload.report(&path, result);
+                } else if Path::new(&path).starts_with(LANG_DIR_PATH) {
+                    locale = Locale::load(locale.code());
+                    locale.load_font(&thread, &mut assets);
+                    hot_reload.report(&path, Ok(()));
+                }
             }
-            GameState::MainMenu => {
-                // FIXME refactor music code
-                if !main_menu_bgm.is_stream_playing() {
-                    main_menu_bgm.play_stream();
-                } else if main_menu_bgm.is_stream_playing()
-                    && main_menu_bgm.get_time_played() > 49f32
-                {
-                    main_menu_bgm.seek_stream(1f32);
+
+            // POINTER, gameplay is keyboard only so cursor is hidden there
+            let is_playing: bool = matches!(game_state, GameState::Playing);
+            if is_playing != rl.is_cursor_hidden() {
+                if is_playing {
+                    rl.hide_cursor();
                 } else {
-                    main_menu_bgm.update_stream();
+                    rl.show_cursor();
                 }
-                main_menu.update(&mut rl, &mut gd, &delta_time, &mut cam, &mut game_state);
             }
-            GameState::Playing => {
-                // play
+
+            // DEVELOPER CONSOLE
+            console.update(
+                &mut rl,
+                &frame_time,
+                &mut ConsoleContext {
+                    run: &mut run,
+                    game_state: &mut game_state,
+                    cheats: &mut dev_cheats,
+                },
+            );
+            if dev_cheats.reload_requested {
+                dev_cheats.reload_requested = false;
+                shaders.load(&mut rl, &thread, &assets, FOG_SHADER);
+                stage_script = StageScript::load(run.stage);
+                background.reset();
+                player.reset();
+                locale = Locale::load(locale.code());
+                locale.load_font(&thread, &mut assets);
+                player_atlas = SpriteAtlas::load(&mut rl, &thread, &mut assets, PLAYER_ATLAS);
             }
-            GameState::GameOver => {
-                // GameOver
+
+            // SCREENSHOT, taken after this frame is drawn
+            if rl.is_key_pressed(gd.key("screenshot")) {
+                screenshots.request();
             }
-            GameState::EndScreen => {
-                // EndScreen
+            screenshots.update(&frame_time);
+
+            // UPDATE
+            match game_state {
+                // Keyboard belongs to the console while it is open
+                _ if console.is_open() => {}
+                // include upd + draw to each state since they have different logic
+                // and drawing tasks
+                GameState::GreetingScreen => {
+                    greet_screen.update(&rl, &delta_time, &mut cam, &mut game_state);
+                }
+                GameState::MainMenu => {
+                    // FIXME refactor music code
+                    if let Some(main_menu_bgm) = assets.music(MENU_BGM_ID) {
+                        let track: Option<TrackInfo> = tracks.get(MENU_BGM_ID);
+                        if !main_menu_bgm.is_stream_playing() {
+                            main_menu_bgm.play_stream();
+                        } else if let Some(track) = track
+                            .filter(|track| main_menu_bgm.get_time_played() > track.loop_end)
+                        {
+                            main_menu_bgm.seek_stream(track.loop_start);
+                        } else {
+                            main_menu_bgm.update_stream();
+                        }
+                    }
+                    main_menu.update(
+                        &mut rl,
+                        &mut gd,
+                        &locale,
+                        &delta_time,
+                        &mut cam,
+                        &mut game_state,
+                        &mut run,
+                    );
+                }
+                GameState::Playing => {
+                    // play, in fixed steps so frame rate and game speed don't change the run
+                    for _ in 0..fixed_step.steps(&delta_time) {
+                        let input: u8 = match replay_player.as_mut() {
+                            Some(playback) => match playback.next_input() {
+                                Some(input) => input,
+                                None => {
+                                    log_info!(
+                                        "Replay is over, state hash {:016x}",
+                                        gameplay_hash(&run, &player, &background)
+                                    );
+                                    replay_player = None;
+                                    game_state = GameState::MainMenu;
+                                    break;
+                                }
+                            },
+                            None => input_from_keys(&rl, &gd),
+                        };
+                        let outcome: StepOutcome = step_gameplay(
+                            input,
+                            &mut run,
+                            &mut stage_script,
+                            &mut background,
+                            &mut player,
+                            player_atlas.as_ref(),
+                        );
+                        if outcome == StepOutcome::Playing {
+                            continue;
+                        }
+                        // A watched replay ends with the run, screens after it are for players
+                        game_state = match (replay_player.take(), outcome) {
+                            (Some(_), _) => GameState::MainMenu,
+                            (None, StepOutcome::GameOver) => GameState::GameOver,
+                            (None, StepOutcome::Cleared) => GameState::EndScreen,
+                            (None, StepOutcome::Playing) => GameState::Playing,
+                        };
+                        break;
+                    }
+                    hud.update(&delta_time, &run);
+                }
+                GameState::GameOver => {
+                    game_over_screen.update(
+                        &mut rl,
+                        &mut gd,
+                        &locale,
+                        &delta_time,
+                        &mut run,
+                        &mut scores,
+                        &mut game_state,
+                    );
+                }
+                GameState::EndScreen => {
+                    end_screen.update(
+                        &mut rl,
+                        &thread,
+                        &audio,
+                        &gd,
+                        &delta_time,
+                        &run,
+                        &mut save_data,
+                        &mut game_state,
+                    );
+                }
             }
-        }
 
-        // DRAW out of canvas
-        let mut d = rl.begin_drawing(&thread);
-        d.clear_background(Color::BLACK);
-
-        // DRAW IN CANVAS
-        // STATE MANAGING
-        match game_state {
-            // include upd + draw to each state since they have different logic
-            // and drawing tasks
-            GameState::GreetingScreen => {
-                greet_screen.draw(&thread, &mut d, &font, &cam, &mut render_target);
+            #[cfg(feature = "debug_overlay")]
+            debug_overlay.end_update();
+
+            // STATE TRANSITIONS
+            if game_state != logged_game_state {
+                log_info!("Game state {:?} -> {:?}", logged_game_state, game_state);
+                logged_game_state = game_state;
+                set_crash_context(game_state, &gd);
             }
-            GameState::MainMenu => {
-                main_menu.draw(&thread, &mut d, &gd, &font, &cam, &mut render_target);
+
+            // LANGUAGE, changed in Option menu or by options reload
+            if gd.language() != locale.code() {
+                locale = Locale::load(gd.language());
+                locale.load_font(&thread, &mut assets);
+                if gd.language() != locale.code() {
+                    gd.set_language(locale.code());
+                }
             }
-            GameState::Playing => {
-                //playing
+            // SCALE FILTER, changed in Option menu or by options reload
+            if gd.scale_filter() != scale_filter {
+                scale_filter = gd.scale_filter();
+                render_target.set_texture_filter(&thread, scale_filter.texture_filter());
             }
-            GameState::GameOver => {
-                //game_over
+            let font: &Font = assets.font(locale.font_id());
+
+            // DRAW out of canvas
+            #[cfg(feature = "debug_overlay")]
+            debug_overlay.begin_draw();
+            let mut d = rl.begin_drawing(&thread);
+            d.clear_background(Color::BLACK);
+
+            // DRAW IN CANVAS
+            // STATE MANAGING
+            match game_state {
+                // include upd + draw to each state since they have different logic
+                // and drawing tasks
+                GameState::GreetingScreen => {
+                    greet_screen.draw(
+                        &thread,
+                        &mut d,
+                        &gd,
+                        font,
+                        &locale,
+                        &cam,
+                        &mut shaders,
+                        &mut render_target,
+                    );
+                }
+                GameState::MainMenu => {
+                    main_menu.draw(
+                        &thread,
+                        &mut d,
+                        &gd,
+                        font,
+                        &locale,
+                        &cam,
+                        &mut shaders,
+                        &mut render_target,
+                    );
+                }
+                GameState::Playing => {
+                    {
+                        let mut d = d.begin_texture_mode(&thread, &mut render_target);
+                        d.clear_background(stage_script.fog.color);
+                        shaders.set_fog(&stage_script.fog);
+                        // DRAW 3D BG
+                        {
+                            let mut d = d.begin_mode3D(background.camera(&stage_script, &cam));
+                            background.draw(&mut d, &stage_script, &mut shaders);
+                        }
+                        player.draw(&mut d, player_atlas.as_ref(), &assets);
+                        hud.draw(&mut d, font, &locale, &run, scores.top(run.difficulty));
+                    }
+                    draw_on_target(&mut d, &render_target, gd.scaling_mode());
+                }
+                GameState::GameOver => {
+                    game_over_screen.draw(
+                        &thread,
+                        &mut d,
+                        &gd,
+                        font,
+                        &locale,
+                        &run,
+                        &mut render_target,
+                    );
+                }
+                GameState::EndScreen => {
+                    end_screen.draw(&thread, &mut d, &gd, font, &locale, &mut render_target);
+                }
             }
-            GameState::EndScreen => {
-                //end_screen
+
+            #[cfg(feature = "debug_overlay")]
+            {
+                debug_overlay.end_draw();
+                if debug_overlay.is_shown() {
+                    let info: DebugInfo = DebugInfo {
+                        game_state: format!("{:?}", game_state),
+                        menu_state: main_menu.debug_state(),
+                        // There are no bullet, enemy and item systems yet
+                        bullets: 0usize,
+                        enemies: 0usize,
+                        items: 0usize,
+                        hitboxes: if matches!(game_state, GameState::Playing) {
+                            vec![player.hitbox(player_atlas.as_ref())]
+                        } else {
+                            Vec::new()
+                        },
+                    };
+                    debug_overlay.draw(&mut d, &gd, font, &info);
+                }
             }
-        }
 
-        // Draw FPS if global setting tells so
-        //d.draw_fps(0, 30);
-        if gd.fps_should_draw() {
-            let current_fps: &String = &format!("{} fps", d.get_fps());
-            d.draw_text_ex(
-                &font,
-                current_fps,
-                Vector2::new(3f32, 3f32),
-                32f32,
-                1f32,
-                Color::BLACK,
-            );
-            d.draw_text_ex(
-                &font,
-                current_fps,
-                Vector2::new(2f32, 2f32),
-                32f32,
-                1f32,
-                Color::WHITE,
-            );
+            hot_reload.draw(&mut d, font);
+            console.draw(&mut d, font, &dev_cheats, &run);
+            screenshots.draw(&mut d, font);
+
+            // Draw FPS if global setting tells so
+            //d.draw_fps(0, 30);
+            if gd.fps_should_draw() {
+                let current_fps: &String =
+                    &locale.tr_format("hud.fps", &[&d.get_fps().to_string()]);
+                draw_text(
+                    &mut d,
+                    font,
+                    current_fps,
+                    Vector2::new(2f32, 2f32),
+                    &TextStyle::new(32f32, Color::WHITE).shadow(1f32, Color::BLACK),
+                );
+            }
+
+            // Frame is finished, so the viewport has everything on it
+            drop(d);
+            screenshots.capture(&render_target, &locale);
+        }
+    }));
+    if let Err(payload) = outcome {
+        if game_state == GameState::Playing {
+            save_crash_replay(&run);
         }
+        panic::resume_unwind(payload);
     }
+
+    // Window size and position of this session
+    gd.save_config();
 }
 
 // // DRAW OUT OF VIEWPORT
@@ -1,6 +1,14 @@
 use raylib::prelude::*;
 
 use crate::global::*;
+use crate::locale::*;
+use crate::menu::*;
+use crate::rng::*;
+use crate::run::*;
+use crate::shaders::*;
+use crate::text::*;
+
+const MENU_FOG: Fog = Fog::new(Color::DIMGRAY, 0.05f32);
 
 pub struct MainMenu {
     menu_state: MenuState,
@@ -11,21 +19,24 @@ pub struct MainMenu {
     text_pos_x: f32,
     text_pos_x_mod: f32,
     timer_activity: f32,
-    chosen_index: u8,
     // Option things
     dot_position: Vector2,
-    // KBD Option Settings
-    is_listening: bool,
+    // Pages keep their chosen item, so going back returns to the item that opened the page
+    title_page: MenuPage<MenuAction>,
+    option_page: MenuPage<MenuAction>,
+    option_kbd_page: MenuPage<MenuAction>,
+    option_display_page: MenuPage<MenuAction>,
     // ...
 }
 
+#[derive(Debug)]
 enum MenuActivity {
     Show,
     Idle,
     Hide,
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 enum MenuState {
     Idle,          // Default
     Start,         // 2
@@ -34,9 +45,25 @@ enum MenuState {
     Score,         // 5
     Option,        // 1
     OptionKBD,     // 1.2
+    OptionDisplay, // 1.1
     Quit,          // Quit
 }
 
+/// What buttons of the pages do
+#[derive(Clone, Copy)]
+enum MenuAction {
+    // Pages that are not made yet
+    Unfinished,
+    // New run, difficulty select is not made yet so it is on Normal
+    Start,
+    // Opens page with its first item chosen
+    Open(MenuState),
+    // Goes to the page that opened this one
+    Back(MenuState),
+    ResetOptions,
+    Quit,
+}
+
 impl MainMenu {
     const ACTIVITY_TIME_MIN: f32 = 3f32;
     const ACTIVITY_TIME_MAX: f32 = 5f32;
@@ -53,41 +80,311 @@ impl MainMenu {
             current_activity: MenuActivity::Show,
             // Idle
             activity_direction_right: false,
-            chosen_index: 0u8,
             text_pos_x: Self::INITIAL_TEXT_POS,
             text_pos_x_mod: 32f32,
             timer_activity: Self::ACTIVITY_TIME_MIN,
             // Option
             dot_position: Vector2::new(Self::INITIAL_TEXT_POS, 0f32),
-            // Option KDB
-            is_listening: false,
+            // Pages
+            title_page: Self::title_page(),
+            option_page: Self::option_page(),
+            option_kbd_page: Self::option_kbd_page(),
+            option_display_page: Self::option_display_page(),
+        }
+    }
+
+    // PAGES
+    /// Title menu, items sway left and right while idle
+    fn title_page() -> MenuPage<MenuAction> {
+        MenuPage::new(
+            vec![
+                MenuItem::button("menu.start", MenuAction::Start).sway(-0.2f32, 5f32),
+                MenuItem::button("menu.start_extra", MenuAction::Unfinished).sway(-1.2f32, 0f32),
+                MenuItem::button("menu.start_practice", MenuAction::Unfinished).sway(0.3f32, 0f32),
+                MenuItem::button("menu.score", MenuAction::Unfinished).sway(-0.4f32, 0f32),
+                MenuItem::button("menu.option", MenuAction::Open(MenuState::Option))
+                    .sway(1.2f32, 0f32),
+                MenuItem::button("menu.quit", MenuAction::Quit).sway(0f32, 20f32),
+            ],
+            0f32,
+            0f32,
+        )
+    }
+
+    fn option_page() -> MenuPage<MenuAction> {
+        MenuPage::new(
+            vec![
+                MenuItem::button("option.display", MenuAction::Open(MenuState::OptionDisplay)),
+                MenuItem::new(
+                    "option.fps_limit",
+                    MenuItemKind::Slider {
+                        get: |gd| gd.get_max_fps() as f32,
+                        set: |rl, gd, fps| gd.set_max_fps(rl, fps as u32),
+                        min: 24f32,
+                        max: 480f32,
+                        step: 12f32,
+                        format: |fps| format!("{}", fps),
+                    },
+                ),
+                MenuItem::new(
+                    "option.show_fps",
+                    MenuItemKind::Toggle {
+                        get: |gd| gd.fps_should_draw(),
+                        toggle: |_, gd| gd.fps_should_draw_toggle(),
+                    },
+                ),
+                MenuItem::new(
+                    "option.vsync",
+                    MenuItemKind::Toggle {
+                        get: |gd| gd.is_vsync_enabled(),
+                        toggle: |rl, gd| gd.toggle_vsync(rl),
+                    },
+                ),
+                // TODO: Actually Change BGM and SFX volume
+                MenuItem::new(
+                    "option.bgm",
+                    MenuItemKind::Slider {
+                        get: |gd| gd.get_bgm_volume_prc(),
+                        set: |_, gd, volume| gd.set_bgm_volume(volume / 100f32),
+                        min: 0f32,
+                        max: 100f32,
+                        step: 10f32,
+                        format: |volume| format!("{}%", volume),
+                    },
+                ),
+                MenuItem::new(
+                    "option.sfx",
+                    MenuItemKind::Slider {
+                        get: |gd| gd.get_sfx_volume_prc(),
+                        set: |_, gd, volume| gd.set_sfx_volume(volume / 100f32),
+                        min: 0f32,
+                        max: 100f32,
+                        step: 10f32,
+                        format: |volume| format!("{}%", volume),
+                    },
+                ),
+                // Applied by main loop on the next frame
+                MenuItem::new(
+                    "option.language",
+                    MenuItemKind::Choice {
+                        choices: |locale, _| locale.languages().to_vec(),
+                        get: |gd| gd.language().to_string(),
+                        set: |_, gd, code| gd.set_language(code),
+                    },
+                ),
+                MenuItem::button(
+                    "option.configure_keys",
+                    MenuAction::Open(MenuState::OptionKBD),
+                ),
+                MenuItem::button("option.reset", MenuAction::ResetOptions),
+                MenuItem::button("option.back", MenuAction::Back(MenuState::Idle)),
+            ],
+            -40f32,
+            340f32,
+        )
+    }
+
+    fn option_display_page() -> MenuPage<MenuAction> {
+        MenuPage::new(
+            vec![
+                MenuItem::new(
+                    "option.display_mode",
+                    MenuItemKind::Choice {
+                        choices: |locale, _| {
+                            [
+                                WindowMode::Windowed,
+                                WindowMode::Borderless,
+                                WindowMode::Fullscreen,
+                            ]
+                            .iter()
+                            .map(|mode| {
+                                let key: String = format!("option.{}", mode.as_str());
+                                (mode.as_str().to_string(), locale.tr(&key).to_string())
+                            })
+                            .collect()
+                        },
+                        get: |gd| gd.window_mode().as_str().to_string(),
+                        set: |rl, gd, name| {
+                            if let Some(mode) = WindowMode::from_name(name) {
+                                gd.set_window_mode(rl, mode);
+                            }
+                        },
+                    },
+                ),
+                // Only used by windowed mode
+                MenuItem::new(
+                    "option.window_size",
+                    MenuItemKind::Choice {
+                        choices: |_, gd| {
+                            window_sizes(
+                                get_monitor_width(gd.monitor()),
+                                get_monitor_height(gd.monitor()),
+                            )
+                            .iter()
+                            .map(|(width, height)| {
+                                let size: String = format!("{}x{}", width, height);
+                                (size.clone(), size)
+                            })
+                            .collect()
+                        },
+                        get: |gd| format!("{}x{}", gd.window_size().0, gd.window_size().1),
+                        set: |rl, gd, size| {
+                            if let Some((width, height)) = size.split_once('x') {
+                                if let (Ok(width), Ok(height)) = (width.parse(), height.parse()) {
+                                    gd.set_window_size(rl, width, height);
+                                }
+                            }
+                        },
+                    },
+                ),
+                MenuItem::new(
+                    "option.monitor",
+                    MenuItemKind::Choice {
+                        choices: |locale, _| {
+                            (0..get_monitor_count())
+                                .map(|monitor| {
+                                    let name: String =
+                                        get_monitor_name(monitor).unwrap_or_else(|_| {
+                                            locale.tr_format(
+                                                "option.monitor_number",
+                                                &[&(monitor + 1).to_string()],
+                                            )
+                                        });
+                                    (monitor.to_string(), name)
+                                })
+                                .collect()
+                        },
+                        get: |gd| gd.monitor().to_string(),
+                        set: |rl, gd, monitor| {
+                            if let Ok(monitor) = monitor.parse() {
+                                gd.set_monitor(rl, monitor);
+                            }
+                        },
+                    },
+                ),
+                MenuItem::new(
+                    "option.scaling",
+                    MenuItemKind::Choice {
+                        choices: |locale, _| {
+                            [ScalingMode::Fit, ScalingMode::Integer, ScalingMode::Stretch]
+                                .iter()
+                                .map(|mode| {
+                                    let key: String = format!("option.scaling_{}", mode.as_str());
+                                    (mode.as_str().to_string(), locale.tr(&key).to_string())
+                                })
+                                .collect()
+                        },
+                        get: |gd| gd.scaling_mode().as_str().to_string(),
+                        set: |_, gd, name| {
+                            if let Some(mode) = ScalingMode::from_name(name) {
+                                gd.set_scaling_mode(mode);
+                            }
+                        },
+                    },
+                ),
+                MenuItem::new(
+                    "option.filter",
+                    MenuItemKind::Choice {
+                        choices: |locale, _| {
+                            [ScaleFilter::Bilinear, ScaleFilter::Nearest]
+                                .iter()
+                                .map(|filter| {
+                                    let key: String = format!("option.filter_{}", filter.as_str());
+                                    (filter.as_str().to_string(), locale.tr(&key).to_string())
+                                })
+                                .collect()
+                        },
+                        get: |gd| gd.scale_filter().as_str().to_string(),
+                        // Applied by main loop on the next frame
+                        set: |_, gd, name| {
+                            if let Some(filter) = ScaleFilter::from_name(name) {
+                                gd.set_scale_filter(filter);
+                            }
+                        },
+                    },
+                ),
+                MenuItem::button("option.back", MenuAction::Back(MenuState::Option)),
+            ],
+            -40f32,
+            400f32,
+        )
+    }
+
+    fn option_kbd_page() -> MenuPage<MenuAction> {
+        MenuPage::new(
+            vec![
+                MenuItem::key_bind("keys.up", "up"),
+                MenuItem::key_bind("keys.down", "down"),
+                MenuItem::key_bind("keys.left", "left"),
+                MenuItem::key_bind("keys.right", "right"),
+                MenuItem::key_bind("keys.attack", "attack"),
+                MenuItem::key_bind("keys.bomb", "bomb"),
+                MenuItem::key_bind("keys.slow", "slow"),
+                MenuItem::key_bind("keys.screenshot", "screenshot"),
+                MenuItem::button("option.back", MenuAction::Back(MenuState::Option)),
+            ],
+            -40f32,
+            280f32,
+        )
+    }
+
+    fn page(&self, state: MenuState) -> Option<&MenuPage<MenuAction>> {
+        match state {
+            MenuState::Idle => Some(&self.title_page),
+            MenuState::Option => Some(&self.option_page),
+            MenuState::OptionKBD => Some(&self.option_kbd_page),
+            MenuState::OptionDisplay => Some(&self.option_display_page),
+            _ => None,
+        }
+    }
+
+    fn page_mut(&mut self, state: MenuState) -> Option<&mut MenuPage<MenuAction>> {
+        match state {
+            MenuState::Idle => Some(&mut self.title_page),
+            MenuState::Option => Some(&mut self.option_page),
+            MenuState::OptionKBD => Some(&mut self.option_kbd_page),
+            MenuState::OptionDisplay => Some(&mut self.option_display_page),
+            _ => None,
+        }
+    }
+
+    /// Menu state and activity for the debug overlay
+    #[cfg(feature = "debug_overlay")]
+    pub fn debug_state(&self) -> String {
+        format!("{:?} {:?}", self.menu_state, self.current_activity)
+    }
+
+    fn layout(&self) -> MenuLayout {
+        MenuLayout {
+            x: self.text_pos_x,
+            x_mod: self.text_pos_x_mod,
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn update(
         &mut self,
         rl: &mut RaylibHandle,
         gd: &mut GameData,
+        locale: &Locale,
         delta_time: &f32,
         cam: &mut Camera3D,
         game_state: &mut GameState,
+        run: &mut RunData,
     ) {
         // exammple update
         rl.update_camera(cam, CameraMode::CAMERA_ORBITAL);
 
         match self.menu_state {
             MenuState::Idle => {
-                self.handle_idle_update(rl, gd, delta_time);
+                self.handle_idle_update(rl, gd, locale, delta_time, game_state, run);
             }
             MenuState::Start => {}
             MenuState::StartExtra => {}
             MenuState::StartPractice => {}
             MenuState::Score => {}
-            MenuState::Option => {
-                self.handle_option_update(rl, gd, delta_time);
-            }
-            MenuState::OptionKBD => {
-                self.handle_option_kbd_update(rl, gd, delta_time);
+            MenuState::Option | MenuState::OptionKBD | MenuState::OptionDisplay => {
+                self.handle_option_update(rl, gd, locale, delta_time);
             }
             MenuState::Quit => {
                 gd.window_must_close();
@@ -95,709 +392,87 @@ impl MainMenu {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn draw(
         &self,
         thread: &RaylibThread,
         d: &mut RaylibDrawHandle,
         gd: &GameData,
         font: &Font,
+        locale: &Locale,
         cam: &Camera3D,
+        shaders: &mut ShaderManager,
         render_target: &mut RenderTexture2D,
     ) {
+        shaders.set_fog(&MENU_FOG);
+
         // DRAW IN VIEWPORT
         {
             let mut d = d.begin_texture_mode(thread, render_target);
             d.clear_background(Color::DIMGRAY);
             // Example Text
-            d.draw_text_ex(
+            draw_text(
+                &mut d,
                 font,
-                "Menu",
+                locale.tr("menu.title"),
                 Vector2::new(12f32, 12f32),
-                22f32,
-                1f32,
-                Color::ORANGE,
+                &TextStyle::new(22f32, Color::ORANGE),
             );
 
             // DRAW 3D BG
             {
                 let mut d = d.begin_mode3D(*cam);
-                d.draw_grid(16i32, 1f32);
+                match shaders.get_mut(FOG_SHADER) {
+                    Some(fog_shader) => {
+                        let mut d = d.begin_shader_mode(fog_shader);
+                        d.draw_grid(16i32, 1f32);
+                    }
+                    None => d.draw_grid(16i32, 1f32),
+                }
             }
 
             // Drawing menu
-            {
-                match self.menu_state {
-                    MenuState::Idle => {
-                        const FONT_SIZE: f32 = 84f32;
-                        const TEXT_GAP: f32 = 72f32;
-                        const INACTIVE_WHITE: Color = Color::new(255u8, 255u8, 255u8, 191u8);
-                        const TEXT_POSITION: f32 = SCREEN_HEIGHT as f32 - 32f32;
-
-                        d.draw_text_ex(
-                            font,
-                            "Start",
-                            Vector2::new(
-                                self.text_pos_x - self.text_pos_x_mod * 0.2f32 + 5f32,
-                                TEXT_POSITION - TEXT_GAP * 6f32,
-                            ),
-                            FONT_SIZE,
-                            1f32,
-                            if self.chosen_index == 0 {
-                                Color::WHITE
-                            } else {
-                                INACTIVE_WHITE
-                            },
-                        );
-                        d.draw_text_ex(
-                            font,
-                            "Start Extra",
-                            Vector2::new(
-                                self.text_pos_x - self.text_pos_x_mod * 1.2f32,
-                                TEXT_POSITION - TEXT_GAP * 5f32,
-                            ),
-                            FONT_SIZE,
-                            1f32,
-                            if self.chosen_index == 1 {
-                                Color::WHITE
-                            } else {
-                                INACTIVE_WHITE
-                            },
-                        );
-                        d.draw_text_ex(
-                            font,
-                            "Start Practice",
-                            Vector2::new(
-                                self.text_pos_x + self.text_pos_x_mod * 0.3f32,
-                                TEXT_POSITION - TEXT_GAP * 4f32,
-                            ),
-                            FONT_SIZE,
-                            1f32,
-                            if self.chosen_index == 2 {
-                                Color::WHITE
-                            } else {
-                                INACTIVE_WHITE
-                            },
-                        );
-                        d.draw_text_ex(
-                            font,
-                            "Score",
-                            Vector2::new(
-                                self.text_pos_x - self.text_pos_x_mod * 0.4f32,
-                                TEXT_POSITION - TEXT_GAP * 3f32,
-                            ),
-                            FONT_SIZE,
-                            1f32,
-                            if self.chosen_index == 3 {
-                                Color::WHITE
-                            } else {
-                                INACTIVE_WHITE
-                            },
-                        );
-                        d.draw_text_ex(
-                            font,
-                            "Option",
-                            Vector2::new(
-                                self.text_pos_x + self.text_pos_x_mod * 1.2f32,
-                                TEXT_POSITION - TEXT_GAP * 2f32,
-                            ),
-                            FONT_SIZE,
-                            1f32,
-                            if self.chosen_index == 4 {
-                                Color::WHITE
-                            } else {
-                                INACTIVE_WHITE
-                            },
-                        );
-                        d.draw_text_ex(
-                            font,
-                            "Quit",
-                            Vector2::new(self.text_pos_x + 20f32, TEXT_POSITION - TEXT_GAP),
-                            FONT_SIZE,
-                            1f32,
-                            if self.chosen_index == 5 {
-                                Color::WHITE
-                            } else {
-                                INACTIVE_WHITE
-                            },
-                        );
-                    }
-                    MenuState::Start => {}
-                    MenuState::StartExtra => {}
-                    MenuState::StartPractice => {}
-                    MenuState::Score => {}
-                    MenuState::Option => {
-                        const FONT_SIZE: f32 = 84f32;
-                        const TEXT_GAP: f32 = 72f32;
-                        const INACTIVE_WHITE: Color = Color::new(255u8, 255u8, 255u8, 191u8);
-                        const TEXT_POSITION: f32 = SCREEN_HEIGHT as f32 - 32f32;
-                        d.draw_circle_v(self.dot_position, 8f32, Color::WHITE);
-                        // MODE: Fullscreen Windowed
-                        {
-                            d.draw_text_ex(
-                                font,
-                                "Windowed",
-                                Vector2::new(
-                                    self.text_pos_x - 40f32,
-                                    TEXT_POSITION - TEXT_GAP * 9f32,
-                                ),
-                                FONT_SIZE,
-                                1f32,
-                                if !gd.is_fullscreen() {
-                                    Color::WHITE
-                                } else {
-                                    INACTIVE_WHITE
-                                },
-                            );
-                            d.draw_text_ex(
-                                font,
-                                "Fullscreen",
-                                Vector2::new(
-                                    self.text_pos_x + self.text_pos_x_mod,
-                                    TEXT_POSITION - TEXT_GAP * 9f32,
-                                ),
-                                FONT_SIZE,
-                                1f32,
-                                if gd.is_fullscreen() {
-                                    Color::WHITE
-                                } else {
-                                    INACTIVE_WHITE
-                                },
-                            );
-                        }
-                        // FPS cap
-                        {
-                            d.draw_text_ex(
-                                font,
-                                "FPS Limit",
-                                Vector2::new(
-                                    self.text_pos_x - 40f32,
-                                    TEXT_POSITION - TEXT_GAP * 8f32,
-                                ),
-                                FONT_SIZE,
-                                1f32,
-                                if self.chosen_index == 1 {
-                                    Color::WHITE
-                                } else {
-                                    INACTIVE_WHITE
-                                },
-                            );
-                            d.draw_text_ex(
-                                font,
-                                format!("{}", gd.get_max_fps()).as_str(),
-                                Vector2::new(
-                                    self.text_pos_x + self.text_pos_x_mod,
-                                    TEXT_POSITION - TEXT_GAP * 8f32,
-                                ),
-                                FONT_SIZE,
-                                1f32,
-                                if self.chosen_index == 1 {
-                                    Color::WHITE
-                                } else {
-                                    INACTIVE_WHITE
-                                },
-                            );
-                        }
-                        // Show FPS
-                        {
-                            d.draw_text_ex(
-                                font,
-                                "Show FPS",
-                                Vector2::new(
-                                    self.text_pos_x - 40f32,
-                                    TEXT_POSITION - TEXT_GAP * 7f32,
-                                ),
-                                FONT_SIZE,
-                                1f32,
-                                if self.chosen_index == 2 {
-                                    Color::WHITE
-                                } else {
-                                    INACTIVE_WHITE
-                                },
-                            );
-                            d.draw_text_ex(
-                                font,
-                                "On",
-                                Vector2::new(
-                                    self.text_pos_x + self.text_pos_x_mod,
-                                    TEXT_POSITION - TEXT_GAP * 7f32,
-                                ),
-                                FONT_SIZE,
-                                1f32,
-                                if gd.fps_should_draw() {
-                                    Color::WHITE
-                                } else {
-                                    INACTIVE_WHITE
-                                },
-                            );
-                            d.draw_text_ex(
-                                font,
-                                "Off",
-                                Vector2::new(
-                                    self.text_pos_x + self.text_pos_x_mod + 100f32,
-                                    TEXT_POSITION - TEXT_GAP * 7f32,
-                                ),
-                                FONT_SIZE,
-                                1f32,
-                                if !gd.fps_should_draw() {
-                                    Color::WHITE
-                                } else {
-                                    INACTIVE_WHITE
-                                },
-                            );
-                        }
-                        // V-Sync
-                        {
-                            d.draw_text_ex(
-                                font,
-                                "V-Sync",
-                                Vector2::new(
-                                    self.text_pos_x - 40f32,
-                                    TEXT_POSITION - TEXT_GAP * 6f32,
-                                ),
-                                FONT_SIZE,
-                                1f32,
-                                if self.chosen_index == 3 {
-                                    Color::WHITE
-                                } else {
-                                    INACTIVE_WHITE
-                                },
-                            );
-                            d.draw_text_ex(
-                                font,
-                                "On",
-                                Vector2::new(
-                                    self.text_pos_x + self.text_pos_x_mod,
-                                    TEXT_POSITION - TEXT_GAP * 6f32,
-                                ),
-                                FONT_SIZE,
-                                1f32,
-                                if gd.is_vsync_enabled() {
-                                    Color::WHITE
-                                } else {
-                                    INACTIVE_WHITE
-                                },
-                            );
-                            d.draw_text_ex(
-                                font,
-                                "Off",
-                                Vector2::new(
-                                    self.text_pos_x + self.text_pos_x_mod + 100f32,
-                                    TEXT_POSITION - TEXT_GAP * 6f32,
-                                ),
-                                FONT_SIZE,
-                                1f32,
-                                if !gd.is_vsync_enabled() {
-                                    Color::WHITE
-                                } else {
-                                    INACTIVE_WHITE
-                                },
-                            );
-                        }
-                        // BGM
-                        {
-                            d.draw_text_ex(
-                                font,
-                                "BGM",
-                                Vector2::new(
-                                    self.text_pos_x - 40f32,
-                                    TEXT_POSITION - TEXT_GAP * 5f32,
-                                ),
-                                FONT_SIZE,
-                                1f32,
-                                if self.chosen_index == 4 {
-                                    Color::WHITE
-                                } else {
-                                    INACTIVE_WHITE
-                                },
-                            );
-                            d.draw_text_ex(
-                                font,
-                                format!("{}%", gd.get_bgm_volume_prc()).as_str(),
-                                Vector2::new(
-                                    self.text_pos_x + self.text_pos_x_mod,
-                                    TEXT_POSITION - TEXT_GAP * 5f32,
-                                ),
-                                FONT_SIZE,
-                                1f32,
-                                if self.chosen_index == 4 {
-                                    Color::WHITE
-                                } else {
-                                    INACTIVE_WHITE
-                                },
-                            );
-                        }
-                        // SFX
-                        {
-                            d.draw_text_ex(
-                                font,
-                                "SFX",
-                                Vector2::new(
-                                    self.text_pos_x - 40f32,
-                                    TEXT_POSITION - TEXT_GAP * 4f32,
-                                ),
-                                FONT_SIZE,
-                                1f32,
-                                if self.chosen_index == 5 {
-                                    Color::WHITE
-                                } else {
-                                    INACTIVE_WHITE
-                                },
-                            );
-                            d.draw_text_ex(
-                                font,
-                                format!("{}%", gd.get_sfx_volume_prc()).as_str(),
-                                Vector2::new(
-                                    self.text_pos_x + self.text_pos_x_mod,
-                                    TEXT_POSITION - TEXT_GAP * 4f32,
-                                ),
-                                FONT_SIZE,
-                                1f32,
-                                if self.chosen_index == 5 {
-                                    Color::WHITE
-                                } else {
-                                    INACTIVE_WHITE
-                                },
-                            );
-                        }
-                        d.draw_text_ex(
-                            font,
-                            "Configure Keys",
-                            Vector2::new(self.text_pos_x - 40f32, TEXT_POSITION - TEXT_GAP * 3f32),
-                            FONT_SIZE,
-                            1f32,
-                            if self.chosen_index == 6 {
-                                Color::WHITE
-                            } else {
-                                INACTIVE_WHITE
-                            },
-                        );
-                        d.draw_text_ex(
-                            font,
-                            "Reset",
-                            Vector2::new(self.text_pos_x - 40f32, TEXT_POSITION - TEXT_GAP * 2f32),
-                            FONT_SIZE,
-                            1f32,
-                            if self.chosen_index == 7 {
-                                Color::WHITE
-                            } else {
-                                INACTIVE_WHITE
-                            },
-                        );
-                        d.draw_text_ex(
-                            font,
-                            "Back",
-                            Vector2::new(self.text_pos_x - 40f32, TEXT_POSITION - TEXT_GAP),
-                            FONT_SIZE,
-                            1f32,
-                            if self.chosen_index == 8 {
-                                Color::WHITE
-                            } else {
-                                INACTIVE_WHITE
-                            },
-                        );
-                    }
-                    MenuState::OptionKBD => {
-                        // Drawing OptionKBD
-                        const FONT_SIZE: f32 = 84f32;
-                        const TEXT_GAP: f32 = 72f32;
-                        const INACTIVE_WHITE: Color = Color::new(255u8, 255u8, 255u8, 191u8);
-                        const TEXT_POSITION: f32 = SCREEN_HEIGHT as f32 - 32f32;
-                        d.draw_circle_v(self.dot_position, 8f32, Color::WHITE);
-                        // var used for dynamic key naming
-                        let mut key_str: String;
-
-                        // UP
-                        {
-                            // drawing
-                            d.draw_text_ex(
-                                font,
-                                "Move up",
-                                Vector2::new(
-                                    self.text_pos_x - 40f32,
-                                    TEXT_POSITION - TEXT_GAP * 8f32,
-                                ),
-                                FONT_SIZE,
-                                1f32,
-                                if self.chosen_index == 0 {
-                                    Color::WHITE
-                                } else {
-                                    INACTIVE_WHITE
-                                },
-                            );
+            if let Some(page) = self.page(self.menu_state) {
+                // Title page has no nav dot
+                if self.menu_state != MenuState::Idle {
+                    d.draw_circle_v(self.dot_position, 8f32, Color::WHITE);
+                }
+                page.draw(&mut d, font, locale, gd, self.layout());
+            }
+        }
+        draw_on_target(d, render_target, gd.scaling_mode());
+    }
 
-                            key_str = gd.get_key_as_string(gd.key("up"));
-                            d.draw_text_ex(
-                                font,
-                                if self.is_listening && self.chosen_index == 0 {
-                                    "Listening"
-                                } else {
-                                    &key_str
-                                },
-                                Vector2::new(
-                                    self.text_pos_x + self.text_pos_x_mod,
-                                    TEXT_POSITION - TEXT_GAP * 8f32,
-                                ),
-                                FONT_SIZE,
-                                1f32,
-                                if self.chosen_index == 0 {
-                                    Color::WHITE
-                                } else {
-                                    INACTIVE_WHITE
-                                },
-                            );
-                        }
-                        // Down
-                        {
-                            d.draw_text_ex(
-                                font,
-                                "Move down",
-                                Vector2::new(
-                                    self.text_pos_x - 40f32,
-                                    TEXT_POSITION - TEXT_GAP * 7f32,
-                                ),
-                                FONT_SIZE,
-                                1f32,
-                                if self.chosen_index == 1 {
-                                    Color::WHITE
-                                } else {
-                                    INACTIVE_WHITE
-                                },
-                            );
-                            key_str = gd.get_key_as_string(gd.key("down"));
-                            d.draw_text_ex(
-                                font,
-                                if self.is_listening && self.chosen_index == 1 {
-                                    "Listening"
-                                } else {
-                                    &key_str
-                                },
-                                Vector2::new(
-                                    self.text_pos_x + self.text_pos_x_mod,
-                                    TEXT_POSITION - TEXT_GAP * 7f32,
-                                ),
-                                FONT_SIZE,
-                                1f32,
-                                if self.chosen_index == 1 {
-                                    Color::WHITE
-                                } else {
-                                    INACTIVE_WHITE
-                                },
-                            );
-                        }
-                        // Left
-                        {
-                            d.draw_text_ex(
-                                font,
-                                "Move left",
-                                Vector2::new(
-                                    self.text_pos_x - 40f32,
-                                    TEXT_POSITION - TEXT_GAP * 6f32,
-                                ),
-                                FONT_SIZE,
-                                1f32,
-                                if self.chosen_index == 2 {
-                                    Color::WHITE
-                                } else {
-                                    INACTIVE_WHITE
-                                },
-                            );
-                            key_str = gd.get_key_as_string(gd.key("left"));
-                            d.draw_text_ex(
-                                font,
-                                if self.is_listening && self.chosen_index == 2 {
-                                    "Listening"
-                                } else {
-                                    &key_str
-                                },
-                                Vector2::new(
-                                    self.text_pos_x + self.text_pos_x_mod,
-                                    TEXT_POSITION - TEXT_GAP * 6f32,
-                                ),
-                                FONT_SIZE,
-                                1f32,
-                                if self.chosen_index == 2 {
-                                    Color::WHITE
-                                } else {
-                                    INACTIVE_WHITE
-                                },
-                            );
-                        }
-                        // Right
-                        {
-                            d.draw_text_ex(
-                                font,
-                                "Move right",
-                                Vector2::new(
-                                    self.text_pos_x - 40f32,
-                                    TEXT_POSITION - TEXT_GAP * 5f32,
-                                ),
-                                FONT_SIZE,
-                                1f32,
-                                if self.chosen_index == 3 {
-                                    Color::WHITE
-                                } else {
-                                    INACTIVE_WHITE
-                                },
-                            );
-                            key_str = gd.get_key_as_string(gd.key("right"));
-                            d.draw_text_ex(
-                                font,
-                                if self.is_listening && self.chosen_index == 3 {
-                                    "Listening"
-                                } else {
-                                    &key_str
-                                },
-                                Vector2::new(
-                                    self.text_pos_x + self.text_pos_x_mod,
-                                    TEXT_POSITION - TEXT_GAP * 5f32,
-                                ),
-                                FONT_SIZE,
-                                1f32,
-                                if self.chosen_index == 3 {
-                                    Color::WHITE
-                                } else {
-                                    INACTIVE_WHITE
-                                },
-                            );
-                        }
-                        // Attack
-                        {
-                            d.draw_text_ex(
-                                font,
-                                "Attack",
-                                Vector2::new(
-                                    self.text_pos_x - 40f32,
-                                    TEXT_POSITION - TEXT_GAP * 4f32,
-                                ),
-                                FONT_SIZE,
-                                1f32,
-                                if self.chosen_index == 4 {
-                                    Color::WHITE
-                                } else {
-                                    INACTIVE_WHITE
-                                },
-                            );
-                            key_str = gd.get_key_as_string(gd.key("attack"));
-                            d.draw_text_ex(
-                                font,
-                                if self.is_listening && self.chosen_index == 4 {
-                                    "Listening"
-                                } else {
-                                    &key_str
-                                },
-                                Vector2::new(
-                                    self.text_pos_x + self.text_pos_x_mod,
-                                    TEXT_POSITION - TEXT_GAP * 4f32,
-                                ),
-                                FONT_SIZE,
-                                1f32,
-                                if self.chosen_index == 4 {
-                                    Color::WHITE
-                                } else {
-                                    INACTIVE_WHITE
-                                },
-                            );
-                        }
-                        // Bomb
-                        {
-                            d.draw_text_ex(
-                                font,
-                                "Bomb",
-                                Vector2::new(
-                                    self.text_pos_x - 40f32,
-                                    TEXT_POSITION - TEXT_GAP * 3f32,
-                                ),
-                                FONT_SIZE,
-                                1f32,
-                                if self.chosen_index == 5 {
-                                    Color::WHITE
-                                } else {
-                                    INACTIVE_WHITE
-                                },
-                            );
-                            key_str = gd.get_key_as_string(gd.key("bomb"));
-                            d.draw_text_ex(
-                                font,
-                                if self.is_listening && self.chosen_index == 5 {
-                                    "Listening"
-                                } else {
-                                    &key_str
-                                },
-                                Vector2::new(
-                                    self.text_pos_x + self.text_pos_x_mod,
-                                    TEXT_POSITION - TEXT_GAP * 3f32,
-                                ),
-                                FONT_SIZE,
-                                1f32,
-                                if self.chosen_index == 5 {
-                                    Color::WHITE
-                                } else {
-                                    INACTIVE_WHITE
-                                },
-                            );
-                        }
-                        // Slow
-                        {
-                            d.draw_text_ex(
-                                font,
-                                "Slow",
-                                Vector2::new(
-                                    self.text_pos_x - 40f32,
-                                    TEXT_POSITION - TEXT_GAP * 2f32,
-                                ),
-                                FONT_SIZE,
-                                1f32,
-                                if self.chosen_index == 6 {
-                                    Color::WHITE
-                                } else {
-                                    INACTIVE_WHITE
-                                },
-                            );
-                            key_str = gd.get_key_as_string(gd.key("slow"));
-                            d.draw_text_ex(
-                                font,
-                                if self.is_listening && self.chosen_index == 6 {
-                                    "Listening"
-                                } else {
-                                    &key_str
-                                },
-                                Vector2::new(
-                                    self.text_pos_x + self.text_pos_x_mod,
-                                    TEXT_POSITION - TEXT_GAP * 2f32,
-                                ),
-                                FONT_SIZE,
-                                1f32,
-                                if self.chosen_index == 6 {
-                                    Color::WHITE
-                                } else {
-                                    INACTIVE_WHITE
-                                },
-                            );
-                        }
-                        d.draw_text_ex(
-                            font,
-                            "Back",
-                            Vector2::new(self.text_pos_x - 40f32, TEXT_POSITION - TEXT_GAP),
-                            FONT_SIZE,
-                            1f32,
-                            if self.chosen_index == 7 {
-                                Color::WHITE
-                            } else {
-                                INACTIVE_WHITE
-                            },
-                        );
-                    }
-                    MenuState::Quit => {
-                        // Essentially nothing
-                    }
+    /// Runs action of an activated button
+    fn handle_action(&mut self, rl: &mut RaylibHandle, gd: &mut GameData, action: MenuAction) {
+        match action {
+            MenuAction::Unfinished | MenuAction::Start => {}
+            MenuAction::Open(state) => {
+                if let Some(page) = self.page_mut(state) {
+                    page.chosen_index = 0usize;
                 }
+                self.current_activity = MenuActivity::Hide;
+                self.next_menu_state = state;
+            }
+            MenuAction::Back(state) => {
+                self.current_activity = MenuActivity::Hide;
+                self.next_menu_state = state;
             }
+            MenuAction::ResetOptions => gd.reset_options(rl),
+            MenuAction::Quit => self.menu_state = MenuState::Quit,
         }
-        draw_on_target(d, render_target);
     }
 
     // MAIN
-    fn handle_idle_update(&mut self, rl: &RaylibHandle, gd: &mut GameData, delta_time: &f32) {
+    fn handle_idle_update(
+        &mut self,
+        rl: &mut RaylibHandle,
+        gd: &mut GameData,
+        locale: &Locale,
+        delta_time: &f32,
+        game_state: &mut GameState,
+        run: &mut RunData,
+    ) {
         match self.current_activity {
             MenuActivity::Show => {
                 if self.text_pos_x < Self::TARGET_TEXT_POS {
@@ -830,9 +505,10 @@ impl MainMenu {
                         );
                         if self.text_pos_x_mod >= MAX_POS_MOD {
                             self.activity_direction_right = false;
-                            self.timer_activity = rand::random_range(
-                                Self::ACTIVITY_TIME_MIN..Self::ACTIVITY_TIME_MAX,
-                            );
+                            self.timer_activity = run
+                                .rng
+                                .stream(RngStream::Menu)
+                                .range_f32(Self::ACTIVITY_TIME_MIN, Self::ACTIVITY_TIME_MAX);
                         }
                     } else if !self.activity_direction_right {
                         self.text_pos_x_mod = lerp_e(
@@ -844,52 +520,22 @@ impl MainMenu {
                         );
                         if self.text_pos_x_mod <= -MAX_POS_MOD {
                             self.activity_direction_right = true;
-                            self.timer_activity = rand::random_range(
-                                Self::ACTIVITY_TIME_MIN..Self::ACTIVITY_TIME_MAX,
-                            );
+                            self.timer_activity = run
+                                .rng
+                                .stream(RngStream::Menu)
+                                .range_f32(Self::ACTIVITY_TIME_MIN, Self::ACTIVITY_TIME_MAX);
                         }
                     }
                 }
 
                 // HANDLE INPUT
-                {
-                    if rl.is_key_pressed(gd.key("down")) {
-                        if self.chosen_index == 5u8 {
-                            self.chosen_index = 0u8;
-                        } else {
-                            self.chosen_index += 1;
-                        }
-                    }
-                    if rl.is_key_pressed(gd.key("up")) {
-                        if self.chosen_index == 0u8 {
-                            self.chosen_index = 5u8;
-                        } else {
-                            self.chosen_index -= 1;
-                        }
-                    }
-                    if rl.is_key_pressed(REJECT) || rl.is_key_pressed(gd.key("bomb")) {
-                        self.chosen_index = 5u8;
-                    }
-                }
-
-                // HANDLE CHOISE
-                {
-                    if rl.is_key_pressed(ACCEPT) || rl.is_key_pressed(gd.key("attack")) {
-                        match self.chosen_index {
-                            0 => {}
-                            1 => {}
-                            2 => {}
-                            3 => {}
-                            4 => {
-                                self.current_activity = MenuActivity::Hide;
-                                self.next_menu_state = MenuState::Option;
-                            }
-                            5 => {
-                                self.menu_state = MenuState::Quit;
-                            }
-                            _ => self.chosen_index = 0,
-                        }
+                match self.title_page.update(rl, gd, locale) {
+                    Some(MenuAction::Start) => {
+                        *run = RunData::new(Difficulty::Normal);
+                        *game_state = GameState::Playing;
                     }
+                    Some(action) => self.handle_action(rl, gd, action),
+                    None => {}
                 }
             }
             MenuActivity::Hide => {
@@ -902,214 +548,36 @@ impl MainMenu {
                         Self::LERP_ACCEPTABLE_ERR,
                     );
                 } else {
-                    self.chosen_index = 0;
                     self.text_pos_x_mod = 0f32;
                     self.current_activity = MenuActivity::Show;
                     self.menu_state = self.next_menu_state;
+                    log_debug!("Menu page {:?}", self.menu_state);
                 }
             }
         }
     }
 
     // OPTION
-    fn handle_option_update(&mut self, rl: &mut RaylibHandle, gd: &mut GameData, delta_time: &f32) {
-        const TEXT_GAP: f32 = 72f32;
-        const TEXT_POSITION: f32 = SCREEN_HEIGHT as f32 - 32f32;
-        const MOD_TEXT_POSITION: f32 = 340f32;
-        const LERP_NAVDOT: f32 = 16f32;
-
-        match self.current_activity {
-            MenuActivity::Show => {
-                // Move text on the specified positions
-                if self.text_pos_x < Self::TARGET_TEXT_POS
-                    && self.text_pos_x_mod < MOD_TEXT_POSITION
-                {
-                    self.text_pos_x = lerp_e(
-                        self.text_pos_x,
-                        Self::TARGET_TEXT_POS,
-                        delta_time,
-                        Self::LERP_SPEED,
-                        Self::LERP_ACCEPTABLE_ERR,
-                    );
-                    self.text_pos_x_mod = lerp_e(
-                        self.text_pos_x_mod,
-                        MOD_TEXT_POSITION,
-                        delta_time,
-                        Self::LERP_SPEED,
-                        Self::LERP_ACCEPTABLE_ERR,
-                    );
-                } else {
-                    self.text_pos_x = Self::TARGET_TEXT_POS;
-                    self.current_activity = MenuActivity::Idle;
-                }
-
-                // Handle appearing of the NAV DOT in right place
-                self.dot_position.x = self.text_pos_x - 72f32;
-                self.dot_position.y = (TEXT_POSITION + 40f32) - (TEXT_GAP * 9f32)
-                    + (TEXT_GAP * self.chosen_index as f32);
-            }
-            MenuActivity::Idle => {
-                // Move NAV DOT till on y axis using interpolation_err
-                self.dot_position.y = lerp_e(
-                    self.dot_position.y,
-                    (TEXT_POSITION + 40f32) - (TEXT_GAP * 9f32)
-                        + (TEXT_GAP * self.chosen_index as f32),
-                    delta_time,
-                    LERP_NAVDOT,
-                    Self::LERP_ACCEPTABLE_ERR,
-                );
-
-                // HANDLE INPUT
-                {
-                    if rl.is_key_pressed(gd.key("down")) {
-                        if self.chosen_index == 8u8 {
-                            self.chosen_index = 0u8;
-                        } else {
-                            self.chosen_index += 1;
-                        }
-                    }
-                    if rl.is_key_pressed(gd.key("up")) {
-                        if self.chosen_index == 0u8 {
-                            self.chosen_index = 8u8;
-                        } else {
-                            self.chosen_index -= 1;
-                        }
-                    }
-                    if rl.is_key_pressed(REJECT) || rl.is_key_pressed(gd.key("bomb")) {
-                        self.chosen_index = 8u8;
-                    }
-                }
-
-                // HANDLE INPUT
-                {
-                    if rl.is_key_pressed(gd.key("left")) || rl.is_key_pressed(gd.key("right")) {
-                        match self.chosen_index {
-                            0 => {
-                                // Windowed / Fullscreen
-                                gd.toggle_fullscreen(rl);
-                            }
-                            1 => {
-                                let current_max_fps = gd.get_max_fps();
-                                if rl.is_key_pressed(gd.key("left")) && current_max_fps > 30u32 {
-                                    gd.set_max_fps(rl, current_max_fps - 12u32);
-                                }
-                                if rl.is_key_pressed(gd.key("right")) && current_max_fps < 480 {
-                                    gd.set_max_fps(rl, current_max_fps + 12u32);
-                                }
-                            }
-                            2 => {
-                                // FPS
-                                gd.fps_should_draw_toggle();
-                            }
-                            3 => {
-                                // V-Sync
-                                gd.toggle_vsync(rl);
-                            }
-                            4 => {
-                                // BGM
-                                // TODO: Actually Change BGM volume
-                                if rl.is_key_pressed(gd.key("left")) {
-                                    let bgm_volume: f32 = gd.get_bgm_volume() - 0.1f32;
-                                    //gd.set_bgm_volume(bgm_audio, bgm_volume.clamp(0f32, 1f32));
-                                    gd.set_bgm_volume(bgm_volume.clamp(0f32, 1f32));
-                                }
-                                if rl.is_key_pressed(gd.key("right")) {
-                                    let bgm_volume: f32 = gd.get_bgm_volume() + 0.1f32;
-                                    gd.set_bgm_volume(bgm_volume.clamp(0f32, 1f32));
-                                }
-                            }
-                            5 => {
-                                // SFX
-                                // TODO: Actually Change SFX colume
-                                if rl.is_key_pressed(gd.key("left")) {
-                                    let sfx_volume: f32 = gd.get_sfx_volume() - 0.1f32;
-                                    gd.set_sfx_volume(sfx_volume.clamp(0f32, 1f32));
-                                }
-                                if rl.is_key_pressed(gd.key("right")) {
-                                    let sfx_volume: f32 = gd.get_sfx_volume() + 0.1f32;
-                                    gd.set_sfx_volume(sfx_volume.clamp(0f32, 1f32));
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
-
-                    if rl.is_key_pressed(ACCEPT) || rl.is_key_pressed(gd.key("attack")) {
-                        match self.chosen_index {
-                            6 => {
-                                // Configure Keys
-                                self.current_activity = MenuActivity::Hide;
-                                self.next_menu_state = MenuState::OptionKBD;
-                            }
-                            7 => {
-                                // Reset
-                                gd.reset_options(rl);
-                            }
-                            8 => {
-                                self.current_activity = MenuActivity::Hide;
-                                self.next_menu_state = MenuState::Idle;
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-            }
-            MenuActivity::Hide => {
-                // Hiding Texts
-                if self.text_pos_x > Self::INITIAL_TEXT_POS && self.text_pos_x_mod > 0f32 {
-                    self.text_pos_x = lerp_e(
-                        self.text_pos_x,
-                        Self::INITIAL_TEXT_POS,
-                        delta_time,
-                        Self::LERP_SPEED,
-                        Self::LERP_ACCEPTABLE_ERR,
-                    );
-                    self.text_pos_x_mod = lerp_e(
-                        self.text_pos_x_mod,
-                        0f32,
-                        delta_time,
-                        Self::LERP_SPEED,
-                        Self::LERP_ACCEPTABLE_ERR,
-                    );
-                } else {
-                    if self.next_menu_state == MenuState::Idle {
-                        self.chosen_index = 4;
-                    } else {
-                        self.chosen_index = 0;
-                    }
-                    self.current_activity = MenuActivity::Show;
-                    self.menu_state = self.next_menu_state;
-                    {
-                        // resetting those values to reuse them
-                        self.text_pos_x_mod = 32f32;
-                        self.activity_direction_right = false;
-                        self.timer_activity = Self::ACTIVITY_TIME_MIN;
-                    }
-                }
-
-                // Move NAV DOT till on x axis
-                self.dot_position.x = self.text_pos_x - 72f32;
-            }
-        }
-    }
-
-    fn handle_option_kbd_update(
+    /// Pages with a value column and the nav dot
+    fn handle_option_update(
         &mut self,
         rl: &mut RaylibHandle,
         gd: &mut GameData,
+        locale: &Locale,
         delta_time: &f32,
     ) {
-        const TEXT_GAP: f32 = 72f32;
-        const TEXT_POSITION: f32 = SCREEN_HEIGHT as f32 - 32f32;
-        const MOD_TEXT_POSITION: f32 = 280f32;
         const LERP_NAVDOT: f32 = 16f32;
 
+        let state: MenuState = self.menu_state;
+        let (value_offset, dot_position): (f32, Vector2) = match self.page(state) {
+            Some(page) => (page.value_offset, page.dot_position(self.layout())),
+            None => return,
+        };
+
         match self.current_activity {
             MenuActivity::Show => {
                 // Move text on the specified positions
-                if self.text_pos_x < Self::TARGET_TEXT_POS
-                    && self.text_pos_x_mod < MOD_TEXT_POSITION
-                {
+                if self.text_pos_x < Self::TARGET_TEXT_POS && self.text_pos_x_mod < value_offset {
                     self.text_pos_x = lerp_e(
                         self.text_pos_x,
                         Self::TARGET_TEXT_POS,
@@ -1119,7 +587,7 @@ impl MainMenu {
                     );
                     self.text_pos_x_mod = lerp_e(
                         self.text_pos_x_mod,
-                        MOD_TEXT_POSITION,
+                        value_offset,
                         delta_time,
                         Self::LERP_SPEED,
                         Self::LERP_ACCEPTABLE_ERR,
@@ -1130,93 +598,24 @@ impl MainMenu {
                 }
 
                 // Handle appearing of the NAV DOT in right place
-                self.dot_position.x = self.text_pos_x - 72f32;
-                self.dot_position.y = (TEXT_POSITION + 40f32) - (TEXT_GAP * 8f32)
-                    + (TEXT_GAP * self.chosen_index as f32);
+                self.dot_position = dot_position;
             }
             MenuActivity::Idle => {
                 // Move NAV DOT till on y axis using interpolation_err
                 self.dot_position.y = lerp_e(
                     self.dot_position.y,
-                    (TEXT_POSITION + 40f32) - (TEXT_GAP * 8f32)
-                        + (TEXT_GAP * self.chosen_index as f32),
+                    dot_position.y,
                     delta_time,
                     LERP_NAVDOT,
                     Self::LERP_ACCEPTABLE_ERR,
                 );
 
                 // HANDLE INPUT
-                if !self.is_listening {
-                    if rl.is_key_pressed(gd.key("down")) {
-                        if self.chosen_index == 7u8 {
-                            self.chosen_index = 0u8;
-                        } else {
-                            self.chosen_index += 1;
-                        }
-                    }
-                    if rl.is_key_pressed(gd.key("up")) {
-                        if self.chosen_index == 0u8 {
-                            self.chosen_index = 7u8;
-                        } else {
-                            self.chosen_index -= 1;
-                        }
-                    }
-                    if rl.is_key_pressed(REJECT) || rl.is_key_pressed(gd.key("bomb")) {
-                        self.chosen_index = 7u8;
-                    }
-
-                    // HANDLE INPUT
-                    // Sets listening to a new button
-                    if rl.is_key_pressed(ACCEPT) || rl.is_key_pressed(gd.key("attack")) {
-                        match self.chosen_index {
-                            0..=6 => self.is_listening = true,
-                            7 => {
-                                self.current_activity = MenuActivity::Hide;
-                                self.next_menu_state = MenuState::Option;
-                            }
-                            _ => {}
-                        }
-                    }
-                } else {
-                    // HANDLE LISTENING
-                    // If is_listening
-                    let listened_key: Option<KeyboardKey> = rl.get_key_pressed();
-
-                    if listened_key.is_some() {
-                        match self.chosen_index {
-                            0 => {
-                                gd.set_key("up", listened_key.unwrap());
-                                self.is_listening = false
-                            }
-                            1 => {
-                                gd.set_key("down", listened_key.unwrap());
-                                self.is_listening = false
-                            }
-                            2 => {
-                                gd.set_key("left", listened_key.unwrap());
-                                self.is_listening = false
-                            }
-                            3 => {
-                                gd.set_key("right", listened_key.unwrap());
-                                self.is_listening = false
-                            }
-                            4 => {
-                                gd.set_key("attack", listened_key.unwrap());
-                                self.is_listening = false
-                            }
-                            5 => {
-                                gd.set_key("bomb", listened_key.unwrap());
-                                self.is_listening = false
-                            }
-                            6 => {
-                                gd.set_key("slow", listened_key.unwrap());
-                                self.is_listening = false
-                            }
-                            _ => {
-                                self.is_listening = false;
-                            }
-                        }
-                    }
+                let action: Option<MenuAction> = self
+                    .page_mut(state)
+                    .and_then(|page| page.update(rl, gd, locale));
+                if let Some(action) = action {
+                    self.handle_action(rl, gd, action);
                 }
             }
             MenuActivity::Hide => {
@@ -1237,13 +636,9 @@ impl MainMenu {
                         Self::LERP_ACCEPTABLE_ERR,
                     );
                 } else {
-                    if self.next_menu_state == MenuState::Option {
-                        self.chosen_index = 6;
-                    } else {
-                        self.chosen_index = 0;
-                    }
                     self.current_activity = MenuActivity::Show;
                     self.menu_state = self.next_menu_state;
+                    log_debug!("Menu page {:?}", self.menu_state);
                     {
                         // resetting those values to reuse them
                         self.text_pos_x_mod = 32f32;
@@ -0,0 +1,441 @@
+use raylib::prelude::*;
+
+use crate::global::*;
+use crate::locale::*;
+use crate::text::*;
+
+const INACTIVE_WHITE: Color = Color::new(255u8, 255u8, 255u8, 191u8);
+const DISABLED_WHITE: Color = Color::new(255u8, 255u8, 255u8, 80u8);
+
+/// What a menu row shows and changes. Values are read from and written to GameData
+/// through plain functions, so a page is declared as data
+pub enum MenuItemKind<A> {
+    /// Returns its action to the owner of the page
+    Button(A),
+    /// On and Off words, left/right or accept flips it
+    Toggle {
+        get: fn(&GameData) -> bool,
+        toggle: fn(&mut RaylibHandle, &mut GameData),
+    },
+    /// Number in min..=max, left/right or mouse wheel move it by step
+    Slider {
+        get: fn(&GameData) -> f32,
+        set: fn(&mut RaylibHandle, &mut GameData, f32),
+        min: f32,
+        max: f32,
+        step: f32,
+        format: fn(f32) -> String,
+    },
+    /// One of (id, shown name) pairs, left/right or accept cycles through them
+    Choice {
+        choices: fn(&Locale, &GameData) -> Vec<(String, String)>,
+        get: fn(&GameData) -> String,
+        set: fn(&mut RaylibHandle, &mut GameData, &str),
+    },
+    /// Game key of an action, accept starts listening for a new key
+    KeyBind(&'static str),
+}
+
+pub struct MenuItem<A> {
+    // Locale key of the label, args replace its "{}"
+    label: &'static str,
+    label_args: Vec<String>,
+    kind: MenuItemKind<A>,
+    // Disabled items are drawn dimmed and can't be chosen
+    is_enabled: bool,
+    // Label moves by sway * x_mod of the layout, and is shifted by offset
+    sway: f32,
+    offset: f32,
+}
+
+impl<A: Copy> MenuItem<A> {
+    pub fn new(label: &'static str, kind: MenuItemKind<A>) -> Self {
+        Self {
+            label,
+            label_args: Vec::new(),
+            kind,
+            is_enabled: true,
+            sway: 0f32,
+            offset: 0f32,
+        }
+    }
+
+    pub fn button(label: &'static str, action: A) -> Self {
+        Self::new(label, MenuItemKind::Button(action))
+    }
+
+    pub fn key_bind(label: &'static str, action: &'static str) -> Self {
+        Self::new(label, MenuItemKind::KeyBind(action))
+    }
+
+    pub fn sway(mut self, sway: f32, offset: f32) -> Self {
+        self.sway = sway;
+        self.offset = offset;
+        self
+    }
+}
+
+/// Where the page is while it slides in and out.
+/// On pages with values x_mod is the value column, on the title page it is the sway
+#[derive(Clone, Copy)]
+pub struct MenuLayout {
+    pub x: f32,
+    pub x_mod: f32,
+}
+
+/// List of items that are laid out bottom up from the bottom of the screen
+pub struct MenuPage<A> {
+    items: Vec<MenuItem<A>>,
+    pub chosen_index: usize,
+    is_listening: bool,
+    // Label position relative to layout x
+    label_offset: f32,
+    // Value column position relative to layout x, where x_mod slides to
+    pub value_offset: f32,
+}
+
+impl<A: Copy> MenuPage<A> {
+    const FONT_SIZE: f32 = 84f32;
+    const TEXT_GAP: f32 = 72f32;
+    const TEXT_POSITION: f32 = SCREEN_HEIGHT as f32 - 32f32;
+    // Second word of a toggle
+    const TOGGLE_GAP: f32 = 100f32;
+    // Nav dot sits left of the label, on its middle
+    const DOT_OFFSET: Vector2 = Vector2::new(-72f32, 40f32);
+
+    pub fn new(items: Vec<MenuItem<A>>, label_offset: f32, value_offset: f32) -> Self {
+        Self {
+            items,
+            chosen_index: 0usize,
+            is_listening: false,
+            label_offset,
+            value_offset,
+        }
+    }
+
+    /// Top of a row
+    pub fn row_y(&self, index: usize) -> f32 {
+        Self::TEXT_POSITION - Self::TEXT_GAP * (self.items.len() - index) as f32
+    }
+
+    /// Nav dot position for the chosen row
+    pub fn dot_position(&self, layout: MenuLayout) -> Vector2 {
+        Vector2::new(layout.x, self.row_y(self.chosen_index)) + Self::DOT_OFFSET
+    }
+
+    /// Handles navigation and item input, returns action of an activated button
+    pub fn update(
+        &mut self,
+        rl: &mut RaylibHandle,
+        gd: &mut GameData,
+        locale: &Locale,
+    ) -> Option<A> {
+        if self.items.is_empty() {
+            return None;
+        }
+        if self.is_listening {
+            self.listen(rl, gd);
+            return None;
+        }
+
+        // Pointer, hovering chooses a row and clicking activates it
+        if let Some(pointer) = pointer_position(rl, gd) {
+            if let Some(index) = self.row_at(pointer.y).filter(|i| self.items[*i].is_enabled) {
+                if pointer_moved(rl) {
+                    self.chosen_index = index;
+                }
+                if index == self.chosen_index {
+                    let wheel: f32 = rl.get_mouse_wheel_move();
+                    if wheel != 0f32 {
+                        self.adjust(rl, gd, locale, wheel.signum() as i32);
+                    }
+                    if rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_RIGHT) {
+                        self.adjust(rl, gd, locale, -1i32);
+                    }
+                    if pointer_pressed(rl) {
+                        return self.click(rl, gd, locale);
+                    }
+                }
+            }
+        }
+
+        if rl.is_key_pressed(gd.key("down")) {
+            self.chosen_index = self.next_enabled(self.chosen_index, 1i32);
+        }
+        if rl.is_key_pressed(gd.key("up")) {
+            self.chosen_index = self.next_enabled(self.chosen_index, -1i32);
+        }
+        // Reject jumps to the last item, which is Back or Quit
+        if rl.is_key_pressed(REJECT) || rl.is_key_pressed(gd.key("bomb")) {
+            self.chosen_index = self.items.len() - 1usize;
+        }
+
+        let direction: i32 = if rl.is_key_pressed(gd.key("left")) {
+            -1i32
+        } else if rl.is_key_pressed(gd.key("right")) {
+            1i32
+        } else {
+            0i32
+        };
+        let is_accepted: bool = rl.is_key_pressed(ACCEPT) || rl.is_key_pressed(gd.key("attack"));
+        if direction != 0 {
+            self.adjust(rl, gd, locale, direction);
+        }
+        if is_accepted {
+            return self.activate(rl, gd, locale);
+        }
+        None
+    }
+
+    /// Left/right on the chosen item
+    pub fn adjust(
+        &mut self,
+        rl: &mut RaylibHandle,
+        gd: &mut GameData,
+        locale: &Locale,
+        direction: i32,
+    ) {
+        let Some(item) = self
+            .items
+            .get(self.chosen_index)
+            .filter(|item| item.is_enabled)
+        else {
+            return;
+        };
+        match &item.kind {
+            MenuItemKind::Toggle { toggle, .. } => toggle(rl, gd),
+            MenuItemKind::Slider {
+                get,
+                set,
+                min,
+                max,
+                step,
+                ..
+            } => {
+                let steps: f32 = ((get(gd) - min) / step).round() + direction as f32;
+                let value: f32 = (min + steps * step).clamp(*min, *max);
+                if value != get(gd) {
+                    set(rl, gd, value);
+                }
+            }
+            MenuItemKind::Choice { choices, get, set } => {
+                let choices: Vec<(String, String)> = choices(locale, gd);
+                if choices.is_empty() {
+                    return;
+                }
+                let current: String = get(gd);
+                let index: i32 = choices
+                    .iter()
+                    .position(|(id, _)| *id == current)
+                    .unwrap_or(0usize) as i32;
+                let next: usize = (index + direction).rem_euclid(choices.len() as i32) as usize;
+                set(rl, gd, &choices[next].0);
+            }
+            MenuItemKind::Button(_) | MenuItemKind::KeyBind(_) => {}
+        }
+    }
+
+    /// Accept on the chosen item
+    pub fn activate(
+        &mut self,
+        rl: &mut RaylibHandle,
+        gd: &mut GameData,
+        locale: &Locale,
+    ) -> Option<A> {
+        let item: &MenuItem<A> = self.items.get(self.chosen_index)?;
+        if !item.is_enabled {
+            return None;
+        }
+        match &item.kind {
+            MenuItemKind::Button(action) => return Some(*action),
+            MenuItemKind::KeyBind(_) => self.is_listening = true,
+            MenuItemKind::Toggle { .. } | MenuItemKind::Choice { .. } => {
+                self.adjust(rl, gd, locale, 1i32)
+            }
+            MenuItemKind::Slider { .. } => {}
+        }
+        None
+    }
+
+    /// Left click on the chosen item, sliders step up since they have nothing to activate
+    fn click(&mut self, rl: &mut RaylibHandle, gd: &mut GameData, locale: &Locale) -> Option<A> {
+        if let Some(MenuItemKind::Slider { .. }) =
+            self.items.get(self.chosen_index).map(|item| &item.kind)
+        {
+            self.adjust(rl, gd, locale, 1i32);
+            return None;
+        }
+        self.activate(rl, gd, locale)
+    }
+
+    /// First enabled item after index in direction, wraps around
+    fn next_enabled(&self, index: usize, direction: i32) -> usize {
+        let count: i32 = self.items.len() as i32;
+        (1..=count)
+            .map(|step| (index as i32 + direction * step).rem_euclid(count) as usize)
+            .find(|i| self.items[*i].is_enabled)
+            .unwrap_or(index)
+    }
+
+    /// Row under the viewport y position
+    fn row_at(&self, y: f32) -> Option<usize> {
+        (0..self.items.len()).find(|i| {
+            let row_y: f32 = self.row_y(*i);
+            y >= row_y && y < row_y + Self::TEXT_GAP
+        })
+    }
+
+    /// Next pressed key goes to the chosen key binding
+    fn listen(&mut self, rl: &mut RaylibHandle, gd: &mut GameData) {
+        if let Some(key) = rl.get_key_pressed() {
+            if let Some(MenuItemKind::KeyBind(action)) =
+                self.items.get(self.chosen_index).map(|item| &item.kind)
+            {
+                gd.set_key(action, key);
+            }
+            self.is_listening = false;
+        }
+    }
+
+    pub fn draw(
+        &self,
+        d: &mut impl RaylibDraw,
+        font: &Font,
+        locale: &Locale,
+        gd: &GameData,
+        layout: MenuLayout,
+    ) {
+        let value_x: f32 = layout.x + layout.x_mod;
+        for (i, item) in self.items.iter().enumerate() {
+            let y: f32 = self.row_y(i);
+            let is_chosen: bool = self.chosen_index == i;
+            let color: Color = if !item.is_enabled {
+                DISABLED_WHITE
+            } else if is_chosen {
+                Color::WHITE
+            } else {
+                INACTIVE_WHITE
+            };
+            let label: String = if item.label_args.is_empty() {
+                locale.tr(item.label).to_string()
+            } else {
+                let args: Vec<&str> = item.label_args.iter().map(|arg| arg.as_str()).collect();
+                locale.tr_format(item.label, &args)
+            };
+            draw_text(
+                d,
+                font,
+                &label,
+                Vector2::new(
+                    layout.x + self.label_offset + layout.x_mod * item.sway + item.offset,
+                    y,
+                ),
+                &TextStyle::new(Self::FONT_SIZE, color),
+            );
+
+            match &item.kind {
+                MenuItemKind::Button(_) => {}
+                MenuItemKind::Toggle { get, .. } => {
+                    let is_on: bool = get(gd);
+                    for (j, (word, is_shown_on)) in [("option.on", true), ("option.off", false)]
+                        .iter()
+                        .enumerate()
+                    {
+                        draw_text(
+                            d,
+                            font,
+                            locale.tr(word),
+                            Vector2::new(value_x + Self::TOGGLE_GAP * j as f32, y),
+                            &TextStyle::new(
+                                Self::FONT_SIZE,
+                                if is_on == *is_shown_on {
+                                    Color::WHITE
+                                } else {
+                                    INACTIVE_WHITE
+                                },
+                            ),
+                        );
+                    }
+                }
+                MenuItemKind::Slider { get, format, .. } => {
+                    draw_text(
+                        d,
+                        font,
+                        &format(get(gd)),
+                        Vector2::new(value_x, y),
+                        &TextStyle::new(Self::FONT_SIZE, color),
+                    );
+                }
+                MenuItemKind::Choice { choices, get, .. } => {
+                    let current: String = get(gd);
+                    let name: String = choices(locale, gd)
+                        .into_iter()
+                        .find(|(id, _)| *id == current)
+                        .map(|(_, name)| name)
+                        .unwrap_or(current);
+                    draw_text(
+                        d,
+                        font,
+                        &name,
+                        Vector2::new(value_x, y),
+                        &TextStyle::new(Self::FONT_SIZE, color),
+                    );
+                }
+                MenuItemKind::KeyBind(action) => {
+                    let key_name: String = gd.get_key_as_string(gd.key(action));
+                    draw_text(
+                        d,
+                        font,
+                        if self.is_listening && is_chosen {
+                            locale.tr("keys.listening")
+                        } else {
+                            &key_name
+                        },
+                        Vector2::new(value_x, y),
+                        &TextStyle::new(Self::FONT_SIZE, color),
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl<A: Copy + PartialEq> MenuPage<A> {
+    fn button_mut(&mut self, action: A) -> Option<&mut MenuItem<A>> {
+        self.items
+            .iter_mut()
+            .find(|item| matches!(item.kind, MenuItemKind::Button(a) if a == action))
+    }
+
+    /// Chooses the button of action
+    pub fn choose(&mut self, action: A) {
+        if let Some(index) = self
+            .items
+            .iter()
+            .position(|item| matches!(item.kind, MenuItemKind::Button(a) if a == action))
+        {
+            self.chosen_index = index;
+        }
+    }
+
+    /// Disabling the chosen button moves the choice to the next enabled one
+    pub fn set_enabled(&mut self, action: A, is_enabled: bool) {
+        if let Some(item) = self.button_mut(action) {
+            item.is_enabled = is_enabled;
+        }
+        if self
+            .items
+            .get(self.chosen_index)
+            .is_some_and(|item| !item.is_enabled)
+        {
+            self.chosen_index = self.next_enabled(self.chosen_index, 1i32);
+        }
+    }
+
+    pub fn set_label(&mut self, action: A, label: &'static str, args: &[String]) {
+        if let Some(item) = self.button_mut(action) {
+            item.label = label;
+            item.label_args = args.to_vec();
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use raylib::prelude::*;
+
+use crate::assets::*;
+use crate::global::*;
+use crate::replay::*;
+use crate::sprite::*;
+
+pub const PLAYER_ATLAS: &str = "player";
+
+/// Player ship, moved by the same input bits that are recorded into replays
+pub struct Player {
+    pub position: Vector2,
+    sprite: AnimatedSprite,
+    is_slow: bool,
+}
+
+impl Player {
+    const SPEED: f32 = 420f32;
+    const SLOW_SPEED: f32 = 180f32;
+    const MARGIN: f32 = 24f32;
+    // Used for the focus dot when atlas has no hitbox
+    const HITBOX_RADIUS: f32 = 4f32;
+    const SPAWN_POSITION: Vector2 =
+        Vector2::new(SCREEN_WIDTH as f32 / 2f32, SCREEN_HEIGHT as f32 - 160f32);
+
+    pub fn new() -> Self {
+        Self {
+            position: Self::SPAWN_POSITION,
+            sprite: AnimatedSprite::new("idle"),
+            is_slow: false,
+        }
+    }
+
+    /// Puts player back to the spawn point
+    pub fn reset(&mut self) {
+        self.position = Self::SPAWN_POSITION;
+        self.sprite.play("idle");
+    }
+
+    pub fn update(&mut self, delta_time: &f32, input: u8, atlas: Option<&SpriteAtlas>) {
+        self.is_slow = input & INPUT_SLOW != 0;
+
+        let mut direction: Vector2 = Vector2::zero();
+        if input & INPUT_UP != 0 {
+            direction.y -= 1f32;
+        }
+        if input & INPUT_DOWN != 0 {
+            direction.y += 1f32;
+        }
+        if input & INPUT_LEFT != 0 {
+            direction.x -= 1f32;
+        }
+        if input & INPUT_RIGHT != 0 {
+            direction.x += 1f32;
+        }
+
+        let speed: f32 = if self.is_slow {
+            Self::SLOW_SPEED
+        } else {
+            Self::SPEED
+        };
+        self.position += direction.normalized() * speed * *delta_time;
+        self.position.x = self
+            .position
+            .x
+            .clamp(Self::MARGIN, SCREEN_WIDTH as f32 - Self::MARGIN);
+        self.position.y = self
+            .position
+            .y
+            .clamp(Self::MARGIN, SCREEN_HEIGHT as f32 - Self::MARGIN);
+
+        self.sprite.play(if direction.x < 0f32 {
+            "left"
+        } else if direction.x > 0f32 {
+            "right"
+        } else {
+            "idle"
+        });
+        if let Some(atlas) = atlas {
+            self.sprite.update(delta_time, atlas);
+        }
+    }
+
+    /// Center and radius of the hitbox, from the current frame of the atlas if it has one
+    pub fn hitbox(&self, atlas: Option<&SpriteAtlas>) -> (Vector2, f32) {
+        atlas
+            .and_then(|atlas| self.sprite.hitbox(atlas, self.position))
+            .unwrap_or((self.position, Self::HITBOX_RADIUS))
+    }
+
+    pub fn draw(
+        &self,
+        d: &mut impl RaylibDraw,
+        atlas: Option<&SpriteAtlas>,
+        assets: &AssetManager,
+    ) {
+        match atlas {
+            Some(atlas) => {
+                self.sprite
+                    .draw(d, atlas, assets, self.position, Color::WHITE);
+            }
+            None => d.draw_circle_v(self.position, Self::MARGIN / 2f32, Color::DARKMAGENTA),
+        }
+
+        // Focus dot shows the real hitbox while moving slowly
+        if self.is_slow {
+            let hitbox: (Vector2, f32) = self.hitbox(atlas);
+            d.draw_circle_v(hitbox.0, hitbox.1 + 1f32, Color::DARKMAGENTA);
+            d.draw_circle_v(hitbox.0, hitbox.1, Color::WHITE);
+        }
+    }
+}
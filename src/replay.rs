@@ -0,0 +1,254 @@
+use raylib::prelude::*;
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::global::*;
+use crate::run::*;
+use crate::user_dirs::*;
+
+const REPLAY_DIR_NAME: &str = "replays";
+const REPLAY_HEADER: &str = "nmf-replay 1";
+
+// INPUT BITS
+pub const INPUT_UP: u8 = 1u8;
+pub const INPUT_DOWN: u8 = 1u8 << 1;
+pub const INPUT_LEFT: u8 = 1u8 << 2;
+pub const INPUT_RIGHT: u8 = 1u8 << 3;
+pub const INPUT_ATTACK: u8 = 1u8 << 4;
+pub const INPUT_BOMB: u8 = 1u8 << 5;
+pub const INPUT_SLOW: u8 = 1u8 << 6;
+
+/// Recorded run: seed and starting point plus one input byte per frame
+pub struct Replay {
+    seed: u64,
+    difficulty: Difficulty,
+    stage: u8,
+    inputs: Vec<u8>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, difficulty: Difficulty, stage: u8) -> Self {
+        Self {
+            seed,
+            difficulty,
+            stage,
+            inputs: Vec::new(),
+        }
+    }
+
+    /// Appends input of a single frame
+    pub fn record(&mut self, input: u8) {
+        self.inputs.push(input);
+    }
+
+    /// Reads replay written by save(), returns what is wrong with the file on failure
+    pub fn load(path: &str) -> Result<Self, String> {
+        let replay_data: String =
+            fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+        let mut lines = replay_data.lines();
+        if lines.next() != Some(REPLAY_HEADER) {
+            return Err(format!("{}: not a replay or unknown version", path));
+        }
+        let mut next_line = |name: &str| {
+            lines
+                .next()
+                .ok_or_else(|| format!("{}: {} is missing", path, name))
+        };
+        let seed: u64 = next_line("seed")?
+            .parse()
+            .map_err(|_| format!("{}: can't parse seed", path))?;
+        let difficulty: Difficulty = next_line("difficulty")?
+            .parse()
+            .ok()
+            .and_then(Difficulty::from_index)
+            .ok_or_else(|| format!("{}: can't parse difficulty", path))?;
+        let stage: u8 = next_line("stage")?
+            .parse()
+            .ok()
+            .filter(|stage| (FIRST_STAGE..=EXTRA_STAGE).contains(stage))
+            .ok_or_else(|| format!("{}: can't parse stage", path))?;
+        let input_data: &str = next_line("inputs").unwrap_or("");
+        // Two hex digits per frame, a lone one means the file was cut
+        if !input_data.len().is_multiple_of(2usize) {
+            return Err(format!("{}: inputs end in the middle of a frame", path));
+        }
+        let inputs: Vec<u8> = (0..input_data.len() / 2usize)
+            .map(|i| u8::from_str_radix(input_data.get(i * 2usize..i * 2usize + 2usize)?, 16).ok())
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(|| format!("{}: can't parse inputs", path))?;
+
+        Ok(Self {
+            seed,
+            difficulty,
+            stage,
+            inputs,
+        })
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    pub fn stage(&self) -> u8 {
+        self.stage
+    }
+
+    /// Input of the frame, None after the last recorded frame
+    pub fn input(&self, frame: usize) -> Option<u8> {
+        self.inputs.get(frame).copied()
+    }
+
+    /// Writes replay into the replays directory, returns path of the written file
+    pub fn save(&self) -> Option<String> {
+        let dir: String = data_path(REPLAY_DIR_NAME);
+        fs::create_dir_all(&dir).ok()?;
+        let timestamp: u64 = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_secs())
+            .unwrap_or(0u64);
+        let path: String = format!("{}/nmf_{}.rpy", dir, timestamp);
+        self.save_to(&path)?;
+        Some(path)
+    }
+
+    /// Writes replay into provided path
+    fn save_to(&self, path: &str) -> Option<()> {
+        let inputs: String = self
+            .inputs
+            .iter()
+            .map(|input| format!("{:02x}", input))
+            .collect();
+        let replay_data: String = format!(
+            "{}\n{}\n{}\n{}\n{}\n",
+            REPLAY_HEADER,
+            self.seed,
+            self.difficulty.index(),
+            self.stage,
+            inputs
+        );
+        fs::write(path, replay_data.as_bytes()).ok()
+    }
+}
+
+/// Feeds inputs of a loaded replay instead of the keyboard
+pub struct ReplayPlayer {
+    replay: Replay,
+    frame: usize,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            frame: 0usize,
+        }
+    }
+
+    /// Run that starts like the recorded one
+    pub fn run(&self) -> RunData {
+        RunData::with_seed(
+            self.replay.difficulty(),
+            self.replay.stage(),
+            self.replay.seed(),
+        )
+    }
+
+    /// Input of the next frame, None when replay is over
+    pub fn next_input(&mut self) -> Option<u8> {
+        let input: Option<u8> = self.replay.input(self.frame);
+        self.frame += 1usize;
+        input
+    }
+}
+
+/// Packs currently held game keys into input bits
+pub fn input_from_keys(rl: &RaylibHandle, gd: &GameData) -> u8 {
+    let mut input: u8 = 0u8;
+    if rl.is_key_down(gd.key("up")) {
+        input |= INPUT_UP;
+    }
+    if rl.is_key_down(gd.key("down")) {
+        input |= INPUT_DOWN;
+    }
+    if rl.is_key_down(gd.key("left")) {
+        input |= INPUT_LEFT;
+    }
+    if rl.is_key_down(gd.key("right")) {
+        input |= INPUT_RIGHT;
+    }
+    if rl.is_key_down(gd.key("attack")) {
+        input |= INPUT_ATTACK;
+    }
+    if rl.is_key_down(gd.key("bomb")) {
+        input |= INPUT_BOMB;
+    }
+    if rl.is_key_down(gd.key("slow")) {
+        input |= INPUT_SLOW;
+    }
+    input
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    fn temp_path(name: &str) -> String {
+        env::temp_dir()
+            .join(format!("nmf_{}_{}.rpy", name, process::id()))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    /// Loads replay file with the given lines after the header
+    fn load_data(name: &str, data: &str) -> Result<Replay, String> {
+        let path: String = temp_path(name);
+        fs::write(&path, format!("{}\n{}", REPLAY_HEADER, data)).unwrap();
+        let replay: Result<Replay, String> = Replay::load(&path);
+        fs::remove_file(&path).ok();
+        replay
+    }
+
+    #[test]
+    fn saved_replay_loads_back() {
+        let path: String = temp_path("roundtrip");
+        let mut replay: Replay = Replay::new(42u64, Difficulty::Hard, 3u8);
+        for input in [0u8, INPUT_UP | INPUT_ATTACK, INPUT_SLOW] {
+            replay.record(input);
+        }
+        replay.save_to(&path).unwrap();
+        let loaded: Replay = Replay::load(&path).unwrap();
+        fs::remove_file(&path).ok();
+        assert_eq!(
+            (loaded.seed(), loaded.difficulty(), loaded.stage()),
+            (42u64, Difficulty::Hard, 3u8)
+        );
+        assert_eq!(loaded.inputs, replay.inputs);
+    }
+
+    #[test]
+    fn cut_inputs_are_rejected() {
+        assert!(load_data("cut", "1\n1\n1\n00115\n").is_err());
+        assert_eq!(
+            load_data("whole", "1\n1\n1\n0011\n").unwrap().inputs,
+            vec![0u8, 0x11u8]
+        );
+    }
+
+    #[test]
+    fn unknown_stage_is_rejected() {
+        for stage in ["0", "8", "255"] {
+            let data: String = format!("1\n1\n{}\n00\n", stage);
+            assert!(load_data("stage", &data).is_err(), "stage {}", stage);
+        }
+        assert_eq!(
+            load_data("stage", "1\n4\n7\n").unwrap().stage(),
+            EXTRA_STAGE
+        );
+    }
+}
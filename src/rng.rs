@@ -0,0 +1,222 @@
+// Step of SplitMix64, that expands seeds into generator states
+const SPLITMIX_STEP: u64 = 0x9e3779b97f4a7c15u64;
+const STREAM_COUNT: usize = 3usize;
+
+/// Independent random sequences. Each one advances only when it is used,
+/// so cosmetic randomness doesn't change what happens in gameplay
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RngStream {
+    // Patterns, drops, everything replays must reproduce
+    Gameplay,
+    // Particles and other effects that don't change the run
+    Cosmetic,
+    Menu,
+}
+
+impl RngStream {
+    pub const ALL: [RngStream; STREAM_COUNT] =
+        [RngStream::Gameplay, RngStream::Cosmetic, RngStream::Menu];
+
+    fn index(&self) -> usize {
+        match self {
+            RngStream::Gameplay => 0usize,
+            RngStream::Cosmetic => 1usize,
+            RngStream::Menu => 2usize,
+        }
+    }
+}
+
+/// xoshiro256** generator, same seed gives the same numbers on every platform
+#[derive(Clone, PartialEq, Debug)]
+pub struct StreamRng {
+    state: [u64; 4],
+}
+
+impl StreamRng {
+    pub fn from_seed(seed: u64) -> Self {
+        let mut splitmix: u64 = seed;
+        let mut next = || {
+            splitmix = splitmix.wrapping_add(SPLITMIX_STEP);
+            let mut z: u64 = splitmix;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9u64);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111ebu64);
+            z ^ (z >> 31)
+        };
+        Self {
+            state: [next(), next(), next(), next()],
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let result: u64 = self.state[1]
+            .wrapping_mul(5u64)
+            .rotate_left(7)
+            .wrapping_mul(9u64);
+        let t: u64 = self.state[1] << 17;
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+        self.state[2] ^= t;
+        self.state[3] = self.state[3].rotate_left(45);
+        result
+    }
+
+    /// Number in 0..1, 1 is excluded
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Number in min..max, max is excluded
+    pub fn range_f32(&mut self, min: f32, max: f32) -> f32 {
+        let value: f32 = min + (max - min) * self.next_f32();
+        // Rounding can reach max when the range is far from zero
+        if value < max {
+            value
+        } else {
+            min
+        }
+    }
+}
+
+/// Random numbers of a run. Every stream is seeded from the run seed,
+/// so a replay seed brings back all of them
+#[derive(Clone, PartialEq, Debug)]
+pub struct GameRng {
+    seed: u64,
+    streams: [StreamRng; STREAM_COUNT],
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            streams: RngStream::ALL.map(|stream| {
+                // Streams start far apart in the seed space
+                let salt: u64 = (stream.index() as u64 + 1u64).wrapping_mul(SPLITMIX_STEP);
+                StreamRng::from_seed(seed ^ salt)
+            }),
+        }
+    }
+
+    pub fn stream(&mut self, stream: RngStream) -> &mut StreamRng {
+        &mut self.streams[stream.index()]
+    }
+
+    /// Generator words of a stream, for state hashes
+    pub fn stream_state(&self, stream: RngStream) -> [u64; 4] {
+        self.streams[stream.index()].state
+    }
+
+    /// Single line of hex words: seed, then the four words of every stream
+    pub fn save_state(&self) -> String {
+        let mut words: Vec<String> = vec![format!("{:016x}", self.seed)];
+        for stream in self.streams.iter() {
+            words.extend(stream.state.iter().map(|word| format!("{:016x}", word)));
+        }
+        words.join(" ")
+    }
+
+    /// Reverse of save_state()
+    pub fn load_state(data: &str) -> Result<Self, String> {
+        let words: Vec<u64> = data
+            .split_whitespace()
+            .map(|word| u64::from_str_radix(word, 16))
+            .collect::<Result<Vec<u64>, _>>()
+            .map_err(|_| format!("Can't parse RNG state '{}'", data))?;
+        if words.len() != 1usize + STREAM_COUNT * 4usize {
+            return Err(format!(
+                "RNG state has {} words, expected {}",
+                words.len(),
+                1usize + STREAM_COUNT * 4usize
+            ));
+        }
+        let mut rng: GameRng = Self::new(words[0]);
+        for (stream, state) in rng.streams.iter_mut().zip(words[1..].chunks(4)) {
+            stream.state.copy_from_slice(state);
+        }
+        Ok(rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draw(rng: &mut GameRng, stream: RngStream, count: usize) -> Vec<u64> {
+        (0..count).map(|_| rng.stream(stream).next_u64()).collect()
+    }
+
+    #[test]
+    fn same_seed_gives_same_numbers() {
+        let mut a: GameRng = GameRng::new(42u64);
+        let mut b: GameRng = GameRng::new(42u64);
+        for stream in RngStream::ALL {
+            assert_eq!(draw(&mut a, stream, 100), draw(&mut b, stream, 100));
+        }
+    }
+
+    #[test]
+    fn known_sequence_does_not_change() {
+        // Changing these numbers breaks every recorded replay
+        let mut rng: StreamRng = StreamRng::from_seed(0u64);
+        assert_eq!(
+            [rng.next_u64(), rng.next_u64(), rng.next_u64()],
+            [
+                0x99ec5f36cb75f2b4u64,
+                0xbf6e1f784956452au64,
+                0x1a5f849d4933e6e0u64
+            ]
+        );
+    }
+
+    #[test]
+    fn other_streams_do_not_advance_gameplay() {
+        let mut quiet: GameRng = GameRng::new(7u64);
+        let mut noisy: GameRng = GameRng::new(7u64);
+        for _ in 0..50 {
+            draw(&mut noisy, RngStream::Cosmetic, 3);
+            draw(&mut noisy, RngStream::Menu, 2);
+            assert_eq!(
+                draw(&mut quiet, RngStream::Gameplay, 1),
+                draw(&mut noisy, RngStream::Gameplay, 1)
+            );
+        }
+    }
+
+    #[test]
+    fn streams_differ() {
+        let mut rng: GameRng = GameRng::new(7u64);
+        let gameplay: Vec<u64> = draw(&mut rng, RngStream::Gameplay, 8);
+        assert_ne!(gameplay, draw(&mut rng, RngStream::Cosmetic, 8));
+        assert_ne!(gameplay, draw(&mut rng, RngStream::Menu, 8));
+    }
+
+    #[test]
+    fn saved_state_continues_the_same_way() {
+        let mut rng: GameRng = GameRng::new(1234u64);
+        draw(&mut rng, RngStream::Gameplay, 17);
+        draw(&mut rng, RngStream::Cosmetic, 5);
+        let mut loaded: GameRng = GameRng::load_state(&rng.save_state()).unwrap();
+        assert_eq!(loaded, rng);
+        for stream in RngStream::ALL {
+            assert_eq!(draw(&mut loaded, stream, 20), draw(&mut rng, stream, 20));
+        }
+    }
+
+    #[test]
+    fn malformed_state_is_rejected() {
+        assert!(GameRng::load_state("").is_err());
+        assert!(GameRng::load_state("zz").is_err());
+        assert!(GameRng::load_state("1 2 3").is_err());
+    }
+
+    #[test]
+    fn ranges_stay_in_bounds() {
+        let mut rng: StreamRng = StreamRng::from_seed(99u64);
+        for _ in 0..10000 {
+            let value: f32 = rng.range_f32(3f32, 5f32);
+            assert!((3f32..5f32).contains(&value));
+        }
+    }
+}
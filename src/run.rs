@@ -0,0 +1,198 @@
+use crate::replay::*;
+use crate::rng::*;
+
+// RUN DEFAULTS
+pub const MAX_CONTINUES: u8 = 3u8;
+pub const FIRST_STAGE: u8 = 1u8;
+// Clearing it ends the run, Extra is its own single stage after it
+pub const FINAL_STAGE: u8 = 6u8;
+pub const EXTRA_STAGE: u8 = 7u8;
+const START_LIVES: u8 = 2u8;
+const START_BOMBS: u8 = 3u8;
+pub const LIFE_PIECES_PER_LIFE: u8 = 3u8;
+pub const BOMB_PIECES_PER_BOMB: u8 = 5u8;
+pub const MAX_POWER: f32 = 4f32;
+const START_POINT_VALUE: u32 = 10000u32;
+
+// DIFFICULTY
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Lunatic,
+    Extra,
+}
+
+impl Difficulty {
+    /// Index used when difficulty is written to a file
+    pub fn index(&self) -> u8 {
+        match self {
+            Difficulty::Easy => 0,
+            Difficulty::Normal => 1,
+            Difficulty::Hard => 2,
+            Difficulty::Lunatic => 3,
+            Difficulty::Extra => 4,
+        }
+    }
+
+    /// Reverse of index(), returns None on unknown index
+    pub fn from_index(index: u8) -> Option<Self> {
+        match index {
+            0 => Some(Difficulty::Easy),
+            1 => Some(Difficulty::Normal),
+            2 => Some(Difficulty::Hard),
+            3 => Some(Difficulty::Lunatic),
+            4 => Some(Difficulty::Extra),
+            _ => None,
+        }
+    }
+
+    /// Reverse of as_str(), ignores case, returns None on unknown name
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            Difficulty::Easy,
+            Difficulty::Normal,
+            Difficulty::Hard,
+            Difficulty::Lunatic,
+            Difficulty::Extra,
+        ]
+        .into_iter()
+        .find(|difficulty| difficulty.as_str().eq_ignore_ascii_case(name))
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+            Difficulty::Lunatic => "Lunatic",
+            Difficulty::Extra => "Extra",
+        }
+    }
+
+    /// Key of the difficulty name in the string tables
+    pub fn text_key(&self) -> &str {
+        match self {
+            Difficulty::Easy => "difficulty.easy",
+            Difficulty::Normal => "difficulty.normal",
+            Difficulty::Hard => "difficulty.hard",
+            Difficulty::Lunatic => "difficulty.lunatic",
+            Difficulty::Extra => "difficulty.extra",
+        }
+    }
+}
+
+// BOSS STATUS
+/// Shown by the HUD while a boss is on screen
+pub struct BossStatus {
+    pub health: f32, // 0..1 of current phase
+    pub spell_name: Option<String>,
+    pub spell_timer: f32,
+}
+
+// RUN DATA
+/// Stage a new run of the difficulty begins at
+pub fn start_stage(difficulty: Difficulty) -> u8 {
+    if difficulty == Difficulty::Extra {
+        EXTRA_STAGE
+    } else {
+        FIRST_STAGE
+    }
+}
+
+/// Data of a single run, lives through Playing, GameOver and EndScreen states
+pub struct RunData {
+    pub difficulty: Difficulty,
+    pub stage: u8,
+    pub score: u64,
+    pub lives: u8,
+    pub life_pieces: u8,
+    pub bombs: u8,
+    pub bomb_pieces: u8,
+    pub power: f32,
+    pub point_value: u32,
+    pub graze: u32,
+    pub bombs_used: u32,
+    pub misses: u32,
+    pub continues_used: u8,
+    // Next gameplay step loads the stage anew and puts player and background at its start
+    pub restart_stage: bool,
+    // Console changed the run, its replay would not play back the same
+    pub is_cheated: bool,
+    // Console "god" cheat, misses are ignored
+    pub is_invincible: bool,
+    pub boss: Option<BossStatus>,
+    pub replay: Replay,
+    // Seeded with the replay seed, gameplay must only use this for randomness
+    pub rng: GameRng,
+}
+
+impl RunData {
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self::with_seed(difficulty, start_stage(difficulty), rand::random())
+    }
+
+    /// Run that starts the stage anew with the seed, the way its replay starts
+    pub fn with_seed(difficulty: Difficulty, stage: u8, seed: u64) -> Self {
+        Self {
+            difficulty,
+            stage,
+            score: 0u64,
+            lives: START_LIVES,
+            life_pieces: 0u8,
+            bombs: START_BOMBS,
+            bomb_pieces: 0u8,
+            power: 0f32,
+            point_value: START_POINT_VALUE,
+            graze: 0u32,
+            bombs_used: 0u32,
+            misses: 0u32,
+            continues_used: 0u8,
+            restart_stage: true,
+            is_cheated: false,
+            is_invincible: false,
+            boss: None,
+            replay: Replay::new(seed, difficulty, stage),
+            rng: GameRng::new(seed),
+        }
+    }
+
+    /// Clearing this stage ends the run with an ending
+    pub fn is_final_stage(&self) -> bool {
+        self.stage >= FINAL_STAGE
+    }
+
+    /// Player was hit and loses a life, the run is over when none are left
+    pub fn miss(&mut self) {
+        if self.is_invincible {
+            return;
+        }
+        self.misses += 1u32;
+        self.lives = self.lives.saturating_sub(1u8);
+    }
+
+    /// Returns amount of continues player still can use
+    pub fn continues_left(&self) -> u8 {
+        MAX_CONTINUES.saturating_sub(self.continues_used)
+    }
+
+    /// Uses one continue. Score is reset and its last digit shows used continues
+    pub fn use_continue(&mut self) {
+        if self.continues_left() == 0 {
+            panic!("No continues left, check continues_left() before calling use_continue()");
+        }
+        self.continues_used += 1;
+        self.score = self.continues_used as u64;
+        self.lives = START_LIVES;
+        self.life_pieces = 0u8;
+        self.bombs = START_BOMBS;
+        self.bomb_pieces = 0u8;
+        self.restart_stage = true;
+    }
+
+    /// Replays are only valid for runs without continues and console cheats
+    pub fn can_save_replay(&self) -> bool {
+        self.continues_used == 0 && !self.is_cheated
+    }
+}
@@ -0,0 +1,55 @@
+use std::fs;
+
+use crate::user_dirs::*;
+
+pub const SAVE_FILE_NAME: &str = "save.dat";
+
+/// Progress that is kept between sessions, one entry per line
+pub struct SaveData {
+    endings_seen: Vec<String>,
+}
+
+impl SaveData {
+    /// Loads save data, empty if file is missing
+    pub fn load() -> Self {
+        let mut save_data: SaveData = Self {
+            endings_seen: Vec::new(),
+        };
+        let path: String = data_path(SAVE_FILE_NAME);
+        let data: String = match fs::read_to_string(&path) {
+            Ok(data) => data,
+            Err(_) => return save_data,
+        };
+
+        // Line format: key value
+        for line in data.lines() {
+            match line.split_once(' ') {
+                Some(("ending", ending_id)) => save_data.endings_seen.push(ending_id.to_string()),
+                _ => log_warn!("Skipping unknown line in {}: '{}'", path, line),
+            }
+        }
+        save_data
+    }
+
+    pub fn save(&self) {
+        let data: String = self
+            .endings_seen
+            .iter()
+            .map(|ending_id| format!("ending {}\n", ending_id))
+            .collect();
+        fs::write(data_path(SAVE_FILE_NAME), data.as_bytes()).ok();
+    }
+
+    /// Returns true if ending was already watched once
+    pub fn has_seen_ending(&self, ending_id: &str) -> bool {
+        self.endings_seen.iter().any(|seen| seen == ending_id)
+    }
+
+    /// Records ending as seen and writes save data
+    pub fn mark_ending_seen(&mut self, ending_id: &str) {
+        if !self.has_seen_ending(ending_id) {
+            self.endings_seen.push(ending_id.to_string());
+            self.save();
+        }
+    }
+}
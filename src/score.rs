@@ -0,0 +1,120 @@
+use std::cmp::Reverse;
+use std::fs;
+
+use crate::run::*;
+use crate::user_dirs::*;
+
+pub const SCORES_FILE_NAME: &str = "scores.dat";
+pub const SCORES_PER_DIFFICULTY: usize = 10usize;
+pub const MAX_NAME_LENGTH: usize = 8usize;
+
+pub struct ScoreEntry {
+    pub name: String,
+    pub score: u64,
+    pub stage: u8,
+    pub difficulty: Difficulty,
+}
+
+/// High score table, keeps SCORES_PER_DIFFICULTY best entries of each difficulty
+pub struct HighScores {
+    entries: Vec<ScoreEntry>,
+}
+
+impl HighScores {
+    /// Loads scores from file, table is empty if file is missing
+    pub fn load() -> Self {
+        let mut high_scores: HighScores = Self {
+            entries: Vec::new(),
+        };
+        let path: String = data_path(SCORES_FILE_NAME);
+        let score_data: String = match fs::read_to_string(&path) {
+            Ok(score_data) => score_data,
+            Err(_) => return high_scores,
+        };
+
+        // Line format: difficulty score stage name
+        for line in score_data.lines() {
+            let mut parts = line.splitn(4, ' ');
+            let difficulty: Option<Difficulty> = parts
+                .next()
+                .and_then(|part| part.parse().ok())
+                .and_then(Difficulty::from_index);
+            let score: Option<u64> = parts.next().and_then(|part| part.parse().ok());
+            let stage: Option<u8> = parts.next().and_then(|part| part.parse().ok());
+            let name: Option<&str> = parts.next();
+
+            if let (Some(difficulty), Some(score), Some(stage), Some(name)) =
+                (difficulty, score, stage, name)
+            {
+                high_scores.entries.push(ScoreEntry {
+                    name: name.to_string(),
+                    score,
+                    stage,
+                    difficulty,
+                });
+            } else {
+                log_warn!("Skipping malformed line in {}: '{}'", path, line);
+            }
+        }
+        high_scores
+    }
+
+    pub fn save(&self) {
+        let score_data: String = self
+            .entries
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{} {} {} {}\n",
+                    entry.difficulty.index(),
+                    entry.score,
+                    entry.stage,
+                    entry.name
+                )
+            })
+            .collect();
+        fs::write(data_path(SCORES_FILE_NAME), score_data.as_bytes()).ok();
+    }
+
+    /// Returns true if score makes it into the table of its difficulty
+    pub fn qualifies(&self, difficulty: Difficulty, score: u64) -> bool {
+        if score == 0 {
+            return false;
+        }
+        let entries: Vec<&ScoreEntry> = self.of_difficulty(difficulty);
+        entries.len() < SCORES_PER_DIFFICULTY || entries.iter().any(|entry| score > entry.score)
+    }
+
+    /// Inserts entry and drops everything that fell out of the table
+    pub fn insert(&mut self, entry: ScoreEntry) {
+        let difficulty: Difficulty = entry.difficulty;
+        self.entries.push(entry);
+        // Stable sort keeps older entries above newer ones with the same score
+        self.entries.sort_by_key(|entry| Reverse(entry.score));
+
+        let mut kept: usize = 0usize;
+        self.entries.retain(|entry| {
+            if entry.difficulty != difficulty {
+                return true;
+            }
+            kept += 1;
+            kept <= SCORES_PER_DIFFICULTY
+        });
+    }
+
+    /// Returns best score of difficulty, 0 if there is none
+    pub fn top(&self, difficulty: Difficulty) -> u64 {
+        self.of_difficulty(difficulty)
+            .first()
+            .map(|entry| entry.score)
+            .unwrap_or(0u64)
+    }
+
+    /// Returns entries of difficulty ordered from best to worst
+    pub fn of_difficulty(&self, difficulty: Difficulty) -> Vec<&ScoreEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.difficulty == difficulty)
+            .collect()
+    }
+}
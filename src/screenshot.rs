@@ -0,0 +1,150 @@
+use raylib::prelude::*;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::locale::*;
+use crate::text::*;
+use crate::user_dirs::*;
+
+const SCREENSHOT_DIR_NAME: &str = "screenshots";
+
+/// Saves viewport into PNG files, and shows a toast when it is done.
+/// Capture is requested during the frame and done after it is drawn
+pub struct Screenshots {
+    is_requested: bool,
+    toast: String,
+    toast_timer: f32,
+}
+
+impl Screenshots {
+    const TOAST_TIME: f32 = 2f32;
+    const TOAST_FADE_TIME: f32 = 0.5f32;
+    const FONT_SIZE: f32 = 28f32;
+    const MARGIN: f32 = 8f32;
+
+    pub fn new() -> Self {
+        Self {
+            is_requested: false,
+            toast: String::new(),
+            toast_timer: 0f32,
+        }
+    }
+
+    /// Screenshot will be taken at the end of the frame
+    pub fn request(&mut self) {
+        self.is_requested = true;
+    }
+
+    pub fn update(&mut self, delta_time: &f32) {
+        if self.toast_timer > 0f32 {
+            self.toast_timer -= delta_time;
+        }
+    }
+
+    /// Saves unscaled viewport if screenshot was requested.
+    /// Must be called after drawing ends, so everything drawn this frame is on it
+    pub fn capture(&mut self, render_target: &RenderTexture2D, locale: &Locale) {
+        if !self.is_requested {
+            return;
+        }
+        self.is_requested = false;
+
+        let result: Result<String, String> = save_screenshot(render_target);
+        self.toast = match result {
+            Ok(path) => {
+                log_info!("Screenshot saved to {}", path);
+                locale.tr_format("screenshot.saved", &[&path])
+            }
+            Err(err) => {
+                log_error!("Screenshot failed: {}", err);
+                locale.tr("screenshot.failed").to_string()
+            }
+        };
+        self.toast_timer = Self::TOAST_TIME;
+    }
+
+    /// Toast in the bottom left corner of the window, it fades out at the end
+    pub fn draw(&self, d: &mut RaylibDrawHandle, font: &Font) {
+        if self.toast_timer <= 0f32 {
+            return;
+        }
+
+        let alpha: f32 = (self.toast_timer / Self::TOAST_FADE_TIME).min(1f32);
+        let style: TextStyle = TextStyle::new(Self::FONT_SIZE, Color::WHITE.alpha(alpha));
+        let size: Vector2 = text_size(font, &self.toast, &style);
+        let position: Vector2 = Vector2::new(
+            Self::MARGIN,
+            d.get_screen_height() as f32 - size.y - Self::MARGIN * 3f32,
+        );
+        d.draw_rectangle_v(
+            position - Vector2::new(Self::MARGIN, Self::MARGIN),
+            size + Vector2::new(Self::MARGIN * 2f32, Self::MARGIN * 2f32),
+            Color::BLACK.alpha(0.8f32 * alpha),
+        );
+        draw_text(d, font, &self.toast, position, &style);
+    }
+}
+
+/// Writes render target into screenshots directory, returns path of the written file
+fn save_screenshot(render_target: &RenderTexture2D) -> Result<String, String> {
+    let dir: String = data_path(SCREENSHOT_DIR_NAME);
+    fs::create_dir_all(&dir).map_err(|err| format!("{}: {}", dir, err))?;
+    let mut image: Image = render_target.load_image().map_err(|err| err.to_string())?;
+    // Render textures are stored upside down
+    image.flip_vertical();
+
+    let name: String = timestamp_name();
+    let mut path: String = format!("{}/{}.png", dir, name);
+    // Several screenshots in one second
+    let mut copy: u32 = 1u32;
+    while Path::new(&path).exists() {
+        copy += 1u32;
+        path = format!("{}/{}_{}.png", dir, name, copy);
+    }
+
+    image.export_image(&path);
+    if Path::new(&path).exists() {
+        Ok(path)
+    } else {
+        Err(format!("{} was not written", path))
+    }
+}
+
+/// "nmf_YYYY-MM-DD_HH-MM-SS" of current UTC time
+fn timestamp_name() -> String {
+    let seconds: u64 = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or(0u64);
+    let days: i64 = (seconds / 86400u64) as i64;
+    let time: u64 = seconds % 86400u64;
+
+    // Days to civil date, from Howard Hinnant's date algorithms
+    let z: i64 = days + 719468i64;
+    let era: i64 = z.div_euclid(146097i64);
+    let day_of_era: i64 = z - era * 146097i64;
+    let year_of_era: i64 = (day_of_era - day_of_era / 1460i64 + day_of_era / 36524i64
+        - day_of_era / 146096i64)
+        / 365i64;
+    let day_of_year: i64 =
+        day_of_era - (365i64 * year_of_era + year_of_era / 4i64 - year_of_era / 100i64);
+    let month_index: i64 = (5i64 * day_of_year + 2i64) / 153i64;
+    let day: i64 = day_of_year - (153i64 * month_index + 2i64) / 5i64 + 1i64;
+    let month: i64 = if month_index < 10i64 {
+        month_index + 3i64
+    } else {
+        month_index - 9i64
+    };
+    let year: i64 = year_of_era + era * 400i64 + if month <= 2i64 { 1i64 } else { 0i64 };
+
+    format!(
+        "nmf_{:04}-{:02}-{:02}_{:02}-{:02}-{:02}",
+        year,
+        month,
+        day,
+        time / 3600u64,
+        time % 3600u64 / 60u64,
+        time % 60u64
+    )
+}
@@ -0,0 +1,141 @@
+use raylib::prelude::*;
+use std::collections::HashMap;
+
+use crate::archive::*;
+use crate::assets::*;
+
+// SHADER NAMES
+pub const FOG_SHADER: &str = "fog";
+
+/// Distance fog parameters, used by the fog shader
+#[derive(Clone, Copy)]
+pub struct Fog {
+    pub color: Color,
+    pub density: f32,
+}
+
+impl Fog {
+    pub const fn new(color: Color, density: f32) -> Self {
+        Self { color, density }
+    }
+}
+
+struct LoadedShader {
+    shader: Shader,
+    // Uniform locations cached by name
+    locations: HashMap<String, i32>,
+}
+
+/// Loads GLSL pairs "<path>.vs" + "<path>.fs" listed in the asset manifest and keeps them by name.
+/// Shaders that failed to load are simply absent, so callers draw without them
+pub struct ShaderManager {
+    shaders: HashMap<String, LoadedShader>,
+    // Id of raylib's default shader, known after the first load
+    default_shader_id: Option<u32>,
+}
+
+impl ShaderManager {
+    pub fn new() -> Self {
+        Self {
+            shaders: HashMap::new(),
+            default_shader_id: None,
+        }
+    }
+
+    /// Loads shader pair by name, returns false if it is missing or failed to compile.
+    /// On failure a previously loaded version of the shader stays in use
+    pub fn load(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        assets: &AssetManager,
+        name: &str,
+    ) -> bool {
+        let path: &str = match assets.path(AssetKind::Shader, name) {
+            Some(path) => path,
+            None => return false,
+        };
+        let vs_path: String = format!("{}.vs", path);
+        let fs_path: String = format!("{}.fs", path);
+
+        let (vs_code, fs_code) = match (read_file_string(&vs_path), read_file_string(&fs_path)) {
+            (Some(vs_code), Some(fs_code)) => (vs_code, fs_code),
+            _ => {
+                log_warn!(
+                    "Shader '{}' is missing ({} / {}), {}",
+                    name,
+                    vs_path,
+                    fs_path,
+                    self.fallback_note(name)
+                );
+                return false;
+            }
+        };
+
+        // On compile or link errors raylib hands back its default shader,
+        // loading without code gives that one to compare with
+        let default_shader_id: u32 = *self
+            .default_shader_id
+            .get_or_insert_with(|| rl.load_shader_from_memory(thread, None, None).id);
+        let shader: Shader = rl.load_shader_from_memory(thread, Some(&vs_code), Some(&fs_code));
+        if !shader.is_shader_valid() || shader.id == default_shader_id {
+            log_error!(
+                "Shader '{}' failed to compile, {}",
+                name,
+                self.fallback_note(name)
+            );
+            return false;
+        }
+
+        self.shaders.insert(
+            name.to_string(),
+            LoadedShader {
+                shader,
+                locations: HashMap::new(),
+            },
+        );
+        true
+    }
+
+    fn fallback_note(&self, name: &str) -> &str {
+        if self.shaders.contains_key(name) {
+            "keeping the previous version"
+        } else {
+            "drawing without it"
+        }
+    }
+
+    /// Returns shader for begin_shader_mode, None if it is not loaded
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Shader> {
+        self.shaders.get_mut(name).map(|loaded| &mut loaded.shader)
+    }
+
+    /// Sets uniform by its name, does nothing if shader or uniform does not exist
+    pub fn set_uniform<S: ShaderV>(&mut self, name: &str, uniform: &str, value: S) {
+        let loaded: &mut LoadedShader = match self.shaders.get_mut(name) {
+            Some(loaded) => loaded,
+            None => return,
+        };
+        let location: i32 = match loaded.locations.get(uniform) {
+            Some(location) => *location,
+            None => {
+                let location: i32 = loaded.shader.get_shader_location(uniform);
+                if location < 0 {
+                    log_warn!("Shader '{}' has no uniform '{}'", name, uniform);
+                }
+                loaded.locations.insert(uniform.to_string(), location);
+                location
+            }
+        };
+        if location >= 0 {
+            loaded.shader.set_shader_value(location, value);
+        }
+    }
+
+    /// Applies fog parameters to the fog shader
+    pub fn set_fog(&mut self, fog: &Fog) {
+        let color: Vector4 = fog.color.color_normalize();
+        self.set_uniform(FOG_SHADER, "fogColor", color);
+        self.set_uniform(FOG_SHADER, "fogDensity", fog.density);
+    }
+}
@@ -0,0 +1,318 @@
+use crate::background::*;
+use crate::player::*;
+use crate::rng::*;
+use crate::run::*;
+use crate::sprite::*;
+use crate::stage_script::*;
+
+/// Gameplay always steps by this, whatever the frame rate, so replays play the same everywhere
+pub const FRAME_TIME: f32 = 1f32 / 60f32;
+// Steps one frame can catch up on, a longer hitch slows the game down instead
+const MAX_STEPS_PER_FRAME: u32 = 5u32;
+const FNV_OFFSET: u64 = 0xcbf29ce484222325u64;
+const FNV_PRIME: u64 = 0x100000001b3u64;
+
+/// Where the run goes after a step
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum StepOutcome {
+    Playing,
+    GameOver, // No lives left
+    Cleared,  // Final stage is over
+}
+
+impl StepOutcome {
+    pub fn as_str(&self) -> &str {
+        match self {
+            StepOutcome::Playing => "playing",
+            StepOutcome::GameOver => "game_over",
+            StepOutcome::Cleared => "cleared",
+        }
+    }
+}
+
+/// Turns variable frame times into whole gameplay steps of FRAME_TIME
+pub struct FixedStep {
+    accumulator: f32,
+}
+
+impl FixedStep {
+    pub fn new() -> Self {
+        Self { accumulator: 0f32 }
+    }
+
+    /// Steps to run for a frame that took frame_time, the rest waits for the next frame
+    pub fn steps(&mut self, frame_time: &f32) -> u32 {
+        self.accumulator += frame_time.max(0f32);
+        let mut steps: u32 = 0u32;
+        while self.accumulator >= FRAME_TIME {
+            if steps == MAX_STEPS_PER_FRAME {
+                self.accumulator = 0f32;
+                break;
+            }
+            self.accumulator -= FRAME_TIME;
+            steps += 1u32;
+        }
+        steps
+    }
+}
+
+/// One FRAME_TIME step of the Playing state, without reading keys and drawing.
+/// The game and the headless runner both use it, so they simulate the same way
+pub fn step_gameplay(
+    input: u8,
+    run: &mut RunData,
+    stage_script: &mut StageScript,
+    background: &mut Background,
+    player: &mut Player,
+    player_atlas: Option<&SpriteAtlas>,
+) -> StepOutcome {
+    if run.restart_stage || stage_script.stage() != run.stage {
+        *stage_script = StageScript::load(run.stage);
+        background.reset();
+        player.reset();
+        run.restart_stage = false;
+    }
+    background.update(&FRAME_TIME, stage_script);
+    run.replay.record(input);
+    player.update(&FRAME_TIME, input, player_atlas);
+
+    if run.lives == 0u8 {
+        return StepOutcome::GameOver;
+    }
+    if background.time() >= stage_script.length {
+        if run.is_final_stage() {
+            return StepOutcome::Cleared;
+        }
+        // Next step loads the next stage
+        run.stage += 1u8;
+        run.boss = None;
+    }
+    StepOutcome::Playing
+}
+
+/// FNV-1a of everything gameplay changes, runs with equal hashes ended the same way
+pub fn gameplay_hash(run: &RunData, player: &Player, background: &Background) -> u64 {
+    let mut hash: u64 = FNV_OFFSET;
+    let mut write = |bytes: &[u8]| {
+        for byte in bytes {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    };
+    write(&[
+        run.stage,
+        run.lives,
+        run.life_pieces,
+        run.bombs,
+        run.bomb_pieces,
+    ]);
+    write(&run.score.to_le_bytes());
+    write(&run.power.to_bits().to_le_bytes());
+    write(&run.point_value.to_le_bytes());
+    write(&run.graze.to_le_bytes());
+    write(&run.bombs_used.to_le_bytes());
+    write(&run.misses.to_le_bytes());
+    // Only gameplay stream, effects may use the others as they like
+    for word in run.rng.stream_state(RngStream::Gameplay) {
+        write(&word.to_le_bytes());
+    }
+    write(&player.position.x.to_bits().to_le_bytes());
+    write(&player.position.y.to_bits().to_le_bytes());
+    write(&background.time().to_bits().to_le_bytes());
+    write(&background.scroll().to_bits().to_le_bytes());
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::replay::*;
+    use raylib::prelude::*;
+
+    fn new_gameplay() -> (RunData, StageScript, Background, Player) {
+        let run: RunData = RunData::new(Difficulty::Normal);
+        let stage_script: StageScript = StageScript::load(run.stage);
+        (run, stage_script, Background::new(), Player::new())
+    }
+
+    #[test]
+    fn run_is_over_when_lives_run_out() {
+        let (mut run, mut stage_script, mut background, mut player) = new_gameplay();
+        let start_lives: u8 = run.lives;
+        let mut step = |run: &mut RunData| {
+            step_gameplay(
+                0u8,
+                run,
+                &mut stage_script,
+                &mut background,
+                &mut player,
+                None,
+            )
+        };
+        assert_eq!(step(&mut run), StepOutcome::Playing);
+        while run.lives > 1u8 {
+            run.miss();
+            assert_eq!(step(&mut run), StepOutcome::Playing);
+        }
+        run.miss();
+        assert_eq!(step(&mut run), StepOutcome::GameOver);
+        assert_eq!(run.misses, start_lives as u32);
+    }
+
+    #[test]
+    fn invincible_run_ignores_misses() {
+        let (mut run, mut stage_script, mut background, mut player) = new_gameplay();
+        let start_lives: u8 = run.lives;
+        run.is_invincible = true;
+        for _ in 0..start_lives {
+            run.miss();
+        }
+        let outcome: StepOutcome = step_gameplay(
+            0u8,
+            &mut run,
+            &mut stage_script,
+            &mut background,
+            &mut player,
+            None,
+        );
+        assert_eq!(outcome, StepOutcome::Playing);
+        assert_eq!((run.lives, run.misses), (start_lives, 0u32));
+    }
+
+    #[test]
+    fn new_run_starts_stage_over() {
+        let (mut run, mut stage_script, mut background, mut player) = new_gameplay();
+        let spawn: Vector2 = player.position;
+        for _ in 0..30 {
+            step_gameplay(
+                INPUT_LEFT | INPUT_UP,
+                &mut run,
+                &mut stage_script,
+                &mut background,
+                &mut player,
+                None,
+            );
+        }
+        assert!(player.position.x < spawn.x && background.time() > FRAME_TIME);
+
+        // Retry on the same stage
+        run = RunData::new(run.difficulty);
+        step_gameplay(
+            0u8,
+            &mut run,
+            &mut stage_script,
+            &mut background,
+            &mut player,
+            None,
+        );
+        assert_eq!((player.position.x, player.position.y), (spawn.x, spawn.y));
+        assert_eq!(background.time(), FRAME_TIME);
+        assert!(!run.restart_stage);
+    }
+
+    #[test]
+    fn reseeded_run_plays_back_from_its_replay() {
+        let (mut run, mut stage_script, mut background, mut player) = new_gameplay();
+        let mut step = |input: u8, run: &mut RunData| {
+            step_gameplay(
+                input,
+                run,
+                &mut stage_script,
+                &mut background,
+                &mut player,
+                None,
+            );
+        };
+        for _ in 0..20 {
+            step(INPUT_RIGHT, &mut run);
+        }
+        run.power = MAX_POWER;
+
+        // Console "seed" mid-run
+        run = RunData::with_seed(run.difficulty, run.stage, 42u64);
+        for frame in 0..40u8 {
+            step(frame % 3u8, &mut run);
+        }
+        let recorded: Replay =
+            std::mem::replace(&mut run.replay, Replay::new(0u64, run.difficulty, 1u8));
+
+        let mut replay_player: ReplayPlayer = ReplayPlayer::new(recorded);
+        let mut replayed: RunData = replay_player.run();
+        let (_, mut stage_script, mut replayed_background, mut replayed_player) = new_gameplay();
+        while let Some(input) = replay_player.next_input() {
+            step_gameplay(
+                input,
+                &mut replayed,
+                &mut stage_script,
+                &mut replayed_background,
+                &mut replayed_player,
+                None,
+            );
+        }
+        assert_eq!(
+            gameplay_hash(&replayed, &replayed_player, &replayed_background),
+            gameplay_hash(&run, &player, &background)
+        );
+    }
+
+    /// Steps with no input until the outcome changes or frames run out
+    fn play(run: &mut RunData, stage_script: &mut StageScript, frames: u32) -> StepOutcome {
+        let mut background: Background = Background::new();
+        let mut player: Player = Player::new();
+        for _ in 0..frames {
+            let outcome: StepOutcome =
+                step_gameplay(0u8, run, stage_script, &mut background, &mut player, None);
+            if outcome != StepOutcome::Playing {
+                return outcome;
+            }
+        }
+        StepOutcome::Playing
+    }
+
+    #[test]
+    fn cleared_stage_leads_to_next_one() {
+        let mut run: RunData = RunData::new(Difficulty::Normal);
+        run.stage = 2u8;
+        let mut stage_script: StageScript = StageScript::load(run.stage);
+        run.restart_stage = false;
+        stage_script.length = FRAME_TIME * 9.5f32;
+        assert_eq!(play(&mut run, &mut stage_script, 10), StepOutcome::Playing);
+        assert_eq!(run.stage, 3u8);
+    }
+
+    #[test]
+    fn cleared_final_stage_ends_run() {
+        for (difficulty, stage) in [
+            (Difficulty::Lunatic, FINAL_STAGE),
+            (Difficulty::Extra, EXTRA_STAGE),
+        ] {
+            let mut run: RunData = RunData::new(difficulty);
+            run.stage = stage;
+            let mut stage_script: StageScript = StageScript::load(run.stage);
+            run.restart_stage = false;
+            stage_script.length = FRAME_TIME * 10f32;
+            assert_eq!(play(&mut run, &mut stage_script, 20), StepOutcome::Cleared);
+            assert_eq!(run.stage, stage);
+        }
+    }
+
+    fn total_steps(frame_time: f32, frames: u32) -> u32 {
+        let mut fixed_step: FixedStep = FixedStep::new();
+        (0..frames).map(|_| fixed_step.steps(&frame_time)).sum()
+    }
+
+    #[test]
+    fn any_frame_rate_gives_same_steps_per_second() {
+        for fps in [30u32, 60u32, 75u32, 144u32, 240u32] {
+            let steps: u32 = total_steps(1f32 / fps as f32, fps);
+            assert!((59u32..=60u32).contains(&steps), "{} fps: {}", fps, steps);
+        }
+    }
+
+    #[test]
+    fn hitch_is_not_caught_up_on() {
+        let mut fixed_step: FixedStep = FixedStep::new();
+        assert_eq!(fixed_step.steps(&2f32), MAX_STEPS_PER_FRAME);
+        assert_eq!(fixed_step.steps(&0f32), 0u32);
+    }
+}
@@ -0,0 +1,386 @@
+use raylib::prelude::*;
+use std::collections::HashMap;
+
+use crate::archive::*;
+use crate::assets::*;
+
+const SPRITES_DIR_PATH: &str = "assets/sprites";
+const DEFAULT_FRAME_DURATION: f32 = 0.1f32;
+
+/// Rectangle on the atlas texture plus optional circular hitbox
+#[derive(Clone, Copy)]
+pub struct Frame {
+    pub source: Rectangle,
+    // Offset from the frame center and radius
+    pub hitbox: Option<(Vector2, f32)>,
+}
+
+/// Sequence of frame names with per-frame durations
+pub struct Animation {
+    pub frames: Vec<(String, f32)>,
+    pub is_looped: bool,
+}
+
+/// Frame and animation names parsed from "assets/sprites/<name>.atlas"
+pub struct AtlasDescriptor {
+    pub texture_id: Option<String>,
+    pub frames: HashMap<String, Frame>,
+    pub animations: HashMap<String, Animation>,
+}
+
+impl AtlasDescriptor {
+    /// Parses descriptor, malformed lines are reported and skipped
+    ///
+    /// texture <asset id>
+    /// frame <name> <x> <y> <width> <height>
+    /// hitbox <frame> <offset x> <offset y> <radius>
+    /// anim <name> <loop|once> <frame>[:<seconds>] ...
+    pub fn parse(atlas_name: &str, data: &str) -> Self {
+        let mut descriptor: AtlasDescriptor = Self {
+            texture_id: None,
+            frames: HashMap::new(),
+            animations: HashMap::new(),
+        };
+        // Hitboxes are applied after all frames are known
+        let mut hitboxes: Vec<(String, Vector2, f32)> = Vec::new();
+
+        for (line_index, line) in data.lines().enumerate() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let numbers: Vec<f32> = words
+                .iter()
+                .skip(2)
+                .filter_map(|word| word.parse::<f32>().ok())
+                .collect();
+
+            match words.as_slice() {
+                [] => {}
+                [comment, ..] if comment.starts_with('#') => {}
+                ["texture", id] => descriptor.texture_id = Some(id.to_string()),
+                ["frame", name, _, _, _, _] if numbers.len() == 4 => {
+                    descriptor.frames.insert(
+                        name.to_string(),
+                        Frame {
+                            source: Rectangle::new(numbers[0], numbers[1], numbers[2], numbers[3]),
+                            hitbox: None,
+                        },
+                    );
+                }
+                ["hitbox", name, _, _, _] if numbers.len() == 3 => {
+                    hitboxes.push((
+                        name.to_string(),
+                        Vector2::new(numbers[0], numbers[1]),
+                        numbers[2],
+                    ));
+                }
+                ["anim", name, mode, frames @ ..]
+                    if (*mode == "loop" || *mode == "once") && !frames.is_empty() =>
+                {
+                    let frames: Vec<(String, f32)> = frames
+                        .iter()
+                        .map(|frame| match frame.split_once(':') {
+                            Some((frame, duration)) => match duration.parse::<f32>() {
+                                Ok(duration) => (frame.to_string(), duration),
+                                Err(_) => {
+                                    log_warn!(
+                                        "Atlas '{}' line {}: bad duration '{}' of frame '{}', using {}",
+                                        atlas_name,
+                                        line_index + 1,
+                                        duration,
+                                        frame,
+                                        DEFAULT_FRAME_DURATION
+                                    );
+                                    (frame.to_string(), DEFAULT_FRAME_DURATION)
+                                }
+                            },
+                            None => (frame.to_string(), DEFAULT_FRAME_DURATION),
+                        })
+                        .collect();
+                    descriptor.animations.insert(
+                        name.to_string(),
+                        Animation {
+                            frames,
+                            is_looped: *mode == "loop",
+                        },
+                    );
+                }
+                _ => log_warn!(
+                    "Atlas '{}' line {}: skipping malformed line '{}'",
+                    atlas_name,
+                    line_index + 1,
+                    line
+                ),
+            }
+        }
+
+        for (name, offset, radius) in hitboxes {
+            match descriptor.frames.get_mut(&name) {
+                Some(frame) => frame.hitbox = Some((offset, radius)),
+                None => log_warn!(
+                    "Atlas '{}': hitbox references missing frame '{}'",
+                    atlas_name,
+                    name
+                ),
+            }
+        }
+        descriptor
+    }
+
+    /// Returns "animation -> frame" pairs that reference frames absent from the atlas
+    pub fn missing_frames(&self) -> Vec<(String, String)> {
+        let mut missing: Vec<(String, String)> = Vec::new();
+        for (animation_name, animation) in &self.animations {
+            for (frame_name, _) in &animation.frames {
+                if !self.frames.contains_key(frame_name) {
+                    missing.push((animation_name.clone(), frame_name.clone()));
+                }
+            }
+        }
+        missing.sort();
+        missing
+    }
+}
+
+/// Texture with named frames and animations, texture itself is kept by the asset manager
+pub struct SpriteAtlas {
+    texture_id: String,
+    descriptor: AtlasDescriptor,
+}
+
+impl SpriteAtlas {
+    /// Loads atlas by name, returns None if descriptor is missing.
+    /// Animations with missing frames are reported and dropped
+    pub fn load(
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        assets: &mut AssetManager,
+        name: &str,
+    ) -> Option<Self> {
+        let descriptor_path: String = format!("{}/{}.atlas", SPRITES_DIR_PATH, name);
+        let data: String = match read_file_string(&descriptor_path) {
+            Some(data) => data,
+            None => {
+                log_warn!("Atlas descriptor {} is missing", descriptor_path);
+                return None;
+            }
+        };
+        let mut descriptor: AtlasDescriptor = AtlasDescriptor::parse(name, &data);
+
+        for (animation_name, frame_name) in descriptor.missing_frames() {
+            log_warn!(
+                "Atlas '{}': animation '{}' references missing frame '{}', animation is dropped",
+                name,
+                animation_name,
+                frame_name
+            );
+            descriptor.animations.remove(&animation_name);
+        }
+
+        let texture_id: String = match &descriptor.texture_id {
+            Some(texture_id) => texture_id.clone(),
+            None => {
+                log_warn!("Atlas '{}' has no texture line", name);
+                return None;
+            }
+        };
+        assets.load_texture(rl, thread, &texture_id);
+
+        Some(Self {
+            texture_id,
+            descriptor,
+        })
+    }
+
+    pub fn frame(&self, name: &str) -> Option<&Frame> {
+        self.descriptor.frames.get(name)
+    }
+
+    pub fn animation(&self, name: &str) -> Option<&Animation> {
+        self.descriptor.animations.get(name)
+    }
+
+    /// Draws frame centered on position
+    pub fn draw_frame(
+        &self,
+        d: &mut impl RaylibDraw,
+        assets: &AssetManager,
+        name: &str,
+        position: Vector2,
+        rotation: f32,
+        tint: Color,
+    ) {
+        if let Some(frame) = self.frame(name) {
+            let width: f32 = frame.source.width.abs();
+            let height: f32 = frame.source.height.abs();
+            d.draw_texture_pro(
+                assets.texture(&self.texture_id),
+                frame.source,
+                Rectangle::new(position.x, position.y, width, height),
+                Vector2::new(width / 2f32, height / 2f32),
+                rotation,
+                tint,
+            );
+        }
+    }
+}
+
+/// Plays atlas animations, shared by player, enemies, bullets and items
+pub struct AnimatedSprite {
+    animation: String,
+    frame_index: usize,
+    timer: f32,
+    is_finished: bool,
+}
+
+impl AnimatedSprite {
+    pub fn new(animation: &str) -> Self {
+        Self {
+            animation: animation.to_string(),
+            frame_index: 0usize,
+            timer: 0f32,
+            is_finished: false,
+        }
+    }
+
+    /// Switches animation, does nothing if it is already playing
+    pub fn play(&mut self, animation: &str) {
+        if self.animation != animation {
+            self.animation = animation.to_string();
+            self.frame_index = 0usize;
+            self.timer = 0f32;
+            self.is_finished = false;
+        }
+    }
+
+    pub fn update(&mut self, delta_time: &f32, atlas: &SpriteAtlas) {
+        let animation: &Animation = match atlas.animation(&self.animation) {
+            Some(animation) => animation,
+            None => return,
+        };
+        if self.is_finished {
+            return;
+        }
+
+        self.timer += delta_time;
+        // Several frames can pass during a long frame time
+        while let Some((_, duration)) = animation.frames.get(self.frame_index) {
+            if self.timer < *duration || *duration <= 0f32 {
+                break;
+            }
+            self.timer -= duration;
+            if self.frame_index + 1 < animation.frames.len() {
+                self.frame_index += 1;
+            } else if animation.is_looped {
+                self.frame_index = 0usize;
+            } else {
+                self.is_finished = true;
+                break;
+            }
+        }
+    }
+
+    /// Name of the frame that is shown right now
+    pub fn current_frame<'a>(&self, atlas: &'a SpriteAtlas) -> Option<&'a str> {
+        atlas
+            .animation(&self.animation)?
+            .frames
+            .get(self.frame_index)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Hitbox center and radius in world space for the current frame
+    pub fn hitbox(&self, atlas: &SpriteAtlas, position: Vector2) -> Option<(Vector2, f32)> {
+        let (offset, radius) = atlas.frame(self.current_frame(atlas)?)?.hitbox?;
+        Some((position + offset, radius))
+    }
+
+    pub fn draw(
+        &self,
+        d: &mut impl RaylibDraw,
+        atlas: &SpriteAtlas,
+        assets: &AssetManager,
+        position: Vector2,
+        tint: Color,
+    ) {
+        if let Some(frame) = self.current_frame(atlas) {
+            atlas.draw_frame(d, assets, frame, position, 0f32, tint);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ATLAS: &str = "# player atlas\n\
+                         texture player_sheet\n\
+                         \n\
+                         frame idle_0 0 0 32 48\n\
+                         frame idle_1 32 0 32 48\n\
+                         hitbox idle_0 0 4.5 3\n\
+                         anim idle loop idle_0:0.2 idle_1\n\
+                         anim hit once idle_1:soon\n";
+
+    #[test]
+    fn frames_and_hitboxes_are_parsed() {
+        let descriptor: AtlasDescriptor = AtlasDescriptor::parse("player", ATLAS);
+        assert_eq!(descriptor.texture_id.as_deref(), Some("player_sheet"));
+        assert_eq!(descriptor.frames.len(), 2usize);
+        let source: Rectangle = descriptor.frames["idle_1"].source;
+        assert_eq!(
+            (source.x, source.y, source.width, source.height),
+            (32f32, 0f32, 32f32, 48f32)
+        );
+        let (offset, radius) = descriptor.frames["idle_0"].hitbox.unwrap();
+        assert_eq!((offset.x, offset.y, radius), (0f32, 4.5f32, 3f32));
+        assert!(descriptor.frames["idle_1"].hitbox.is_none());
+    }
+
+    #[test]
+    fn animations_use_default_duration() {
+        let descriptor: AtlasDescriptor = AtlasDescriptor::parse("player", ATLAS);
+        let idle: &Animation = &descriptor.animations["idle"];
+        assert!(idle.is_looped);
+        assert_eq!(
+            idle.frames,
+            vec![
+                ("idle_0".to_string(), 0.2f32),
+                ("idle_1".to_string(), DEFAULT_FRAME_DURATION)
+            ]
+        );
+        // Bad duration falls back to the default too
+        let hit: &Animation = &descriptor.animations["hit"];
+        assert!(!hit.is_looped);
+        assert_eq!(
+            hit.frames,
+            vec![("idle_1".to_string(), DEFAULT_FRAME_DURATION)]
+        );
+    }
+
+    #[test]
+    fn malformed_lines_are_skipped() {
+        let descriptor: AtlasDescriptor = AtlasDescriptor::parse(
+            "broken",
+            "frame a 0 0 16\nframe b 0 0 16 x\nhitbox missing 0 0 2\nanim c bounce a\nanim d loop\n",
+        );
+        assert!(descriptor.texture_id.is_none());
+        assert!(descriptor.frames.is_empty() && descriptor.animations.is_empty());
+    }
+
+    #[test]
+    fn missing_frames_are_listed_in_order() {
+        let descriptor: AtlasDescriptor = AtlasDescriptor::parse(
+            "player",
+            "frame a 0 0 16 16\nanim walk loop a b\nanim fall once z a\n",
+        );
+        assert_eq!(
+            descriptor.missing_frames(),
+            vec![
+                ("fall".to_string(), "z".to_string()),
+                ("walk".to_string(), "b".to_string())
+            ]
+        );
+        assert!(AtlasDescriptor::parse("player", ATLAS)
+            .missing_frames()
+            .is_empty());
+    }
+}
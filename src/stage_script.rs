@@ -0,0 +1,262 @@
+use raylib::prelude::*;
+
+use crate::archive::*;
+use crate::background::*;
+use crate::console::*;
+use crate::global::*;
+use crate::run::*;
+use crate::shaders::*;
+
+pub const STAGES_DIR_PATH: &str = "stages";
+const DEFAULT_FOG: Fog = Fog::new(Color::new(24u8, 28u8, 40u8, 255u8), 0.04f32);
+const DEFAULT_SCROLL_SPEED: f32 = 4f32;
+// Seconds until a stage without "length" is cleared
+const DEFAULT_LENGTH: f32 = 90f32;
+// Spell timer of spell cards started from the console
+const CONSOLE_SPELL_TIME: f32 = 60f32;
+
+/// Per stage data read from "stages/stage_<n>.txt"
+pub struct StageScript {
+    stage: u8,
+    pub fog: Fog,
+    pub scroll_speed: f32,
+    // Stage is cleared when background time reaches it
+    pub length: f32,
+    pub segments: Vec<Segment>,
+    pub camera_path: Vec<CameraKeyframe>,
+    // Lines that failed to parse, shown by the hot reload overlay
+    pub errors: Vec<String>,
+}
+
+impl StageScript {
+    /// Loads script of the stage, missing script or lines fall back to defaults
+    pub fn load(stage: u8) -> Self {
+        let path: String = stage_file_path(stage);
+        let mut script: StageScript = Self {
+            stage,
+            fog: DEFAULT_FOG,
+            scroll_speed: DEFAULT_SCROLL_SPEED,
+            length: DEFAULT_LENGTH,
+            segments: Vec::new(),
+            camera_path: Vec::new(),
+            errors: Vec::new(),
+        };
+
+        let data: String = match read_file_string(&path) {
+            Some(data) => data,
+            None => {
+                log_warn!("{} is missing, stage uses defaults", path);
+                return script;
+            }
+        };
+        script.parse(&path, &data);
+        script
+    }
+
+    pub fn stage(&self) -> u8 {
+        self.stage
+    }
+
+    /// One command per line, "#" starts a comment
+    fn parse(&mut self, path: &str, data: &str) {
+        for (line_number, line) in data.lines().enumerate() {
+            let line: &str = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut args = line.split_whitespace();
+            let command: &str = args.next().unwrap_or("");
+            let args: Vec<&str> = args.collect();
+
+            let is_parsed: bool = match command {
+                // fog <r> <g> <b> <density>
+                "fog" => match parse_fog(&args) {
+                    Some(fog) => {
+                        self.fog = fog;
+                        true
+                    }
+                    None => false,
+                },
+                // scroll <speed>
+                "scroll" => match args.as_slice() {
+                    [speed] => speed.parse().map(|speed| self.scroll_speed = speed).is_ok(),
+                    _ => false,
+                },
+                // length <seconds>
+                "length" => match args.as_slice() {
+                    [length] => match length.parse::<f32>() {
+                        Ok(length) if length > 0f32 => {
+                            self.length = length;
+                            true
+                        }
+                        _ => false,
+                    },
+                    _ => false,
+                },
+                // segment <length>, following primitives belong to it
+                "segment" => match args.as_slice() {
+                    [length] => match length.parse::<f32>() {
+                        Ok(length) if length > 0f32 => {
+                            self.segments.push(Segment {
+                                length,
+                                primitives: Vec::new(),
+                            });
+                            true
+                        }
+                        _ => false,
+                    },
+                    _ => false,
+                },
+                "cube" | "plane" | "cylinder" | "sphere" => {
+                    match (parse_primitive(command, &args), self.segments.last_mut()) {
+                        (Some(primitive), Some(segment)) => {
+                            segment.primitives.push(primitive);
+                            true
+                        }
+                        _ => false,
+                    }
+                }
+                // camera <time> <px> <py> <pz> <tx> <ty> <tz> <fovy> [easing]
+                "camera" => match parse_camera_keyframe(&args) {
+                    Some(keyframe) => {
+                        self.camera_path.push(keyframe);
+                        true
+                    }
+                    None => false,
+                },
+                _ => false,
+            };
+            if !is_parsed {
+                let error: String = format!("{}:{}: can't parse '{}'", path, line_number + 1, line);
+                log_error!("{}", error);
+                self.errors.push(error);
+            }
+        }
+
+        self.camera_path.sort_by(|a, b| a.time.total_cmp(&b.time));
+    }
+}
+
+/// Path of the script file of a stage
+pub fn stage_file_path(stage: u8) -> String {
+    format!("{}/stage_{}.txt", STAGES_DIR_PATH, stage)
+}
+
+fn parse_floats(args: &[&str]) -> Option<Vec<f32>> {
+    args.iter().map(|arg| arg.parse().ok()).collect()
+}
+
+fn parse_color(args: &[&str]) -> Option<Color> {
+    match args {
+        [r, g, b] => Some(Color::new(
+            r.parse().ok()?,
+            g.parse().ok()?,
+            b.parse().ok()?,
+            255u8,
+        )),
+        _ => None,
+    }
+}
+
+/// cube <x> <y> <z> <width> <height> <length> <r> <g> <b>
+/// plane <x> <y> <z> <width> <length> <r> <g> <b>
+/// cylinder <x> <y> <z> <radius> <height> <r> <g> <b>
+/// sphere <x> <y> <z> <radius> <r> <g> <b>
+fn parse_primitive(command: &str, args: &[&str]) -> Option<Primitive> {
+    let (numbers, color) = args.split_at(args.len().checked_sub(3)?);
+    let color: Color = parse_color(color)?;
+    let numbers: Vec<f32> = parse_floats(numbers)?;
+    match (command, numbers.as_slice()) {
+        ("cube", [x, y, z, width, height, length]) => Some(Primitive::Cube {
+            position: Vector3::new(*x, *y, *z),
+            size: Vector3::new(*width, *height, *length),
+            color,
+        }),
+        ("plane", [x, y, z, width, length]) => Some(Primitive::Plane {
+            position: Vector3::new(*x, *y, *z),
+            size: Vector2::new(*width, *length),
+            color,
+        }),
+        ("cylinder", [x, y, z, radius, height]) => Some(Primitive::Cylinder {
+            position: Vector3::new(*x, *y, *z),
+            radius: *radius,
+            height: *height,
+            color,
+        }),
+        ("sphere", [x, y, z, radius]) => Some(Primitive::Sphere {
+            position: Vector3::new(*x, *y, *z),
+            radius: *radius,
+            color,
+        }),
+        _ => None,
+    }
+}
+
+fn parse_camera_keyframe(args: &[&str]) -> Option<CameraKeyframe> {
+    let (numbers, easing) = match args.len() {
+        8 => (args, Easing::Linear),
+        9 => (&args[..8], Easing::from_name(args[8])?),
+        _ => return None,
+    };
+    match parse_floats(numbers)?.as_slice() {
+        [time, px, py, pz, tx, ty, tz, fovy] => Some(CameraKeyframe {
+            time: *time,
+            position: Vector3::new(*px, *py, *pz),
+            target: Vector3::new(*tx, *ty, *tz),
+            fovy: *fovy,
+            easing,
+        }),
+        _ => None,
+    }
+}
+
+fn parse_fog(args: &[&str]) -> Option<Fog> {
+    if args.len() != 4 {
+        return None;
+    }
+    let r: u8 = args[0].parse().ok()?;
+    let g: u8 = args[1].parse().ok()?;
+    let b: u8 = args[2].parse().ok()?;
+    let density: f32 = args[3].parse().ok()?;
+    Some(Fog::new(Color::new(r, g, b, 255u8), density))
+}
+
+/// Console commands that jump into stages and spell cards
+pub fn stage_commands() -> Vec<ConsoleCommand> {
+    vec![
+        ConsoleCommand {
+            name: "stage",
+            usage: "stage <1..7>",
+            run: |context, args| {
+                let stage: u8 = parse_arg(args, 0usize)?;
+                if !(FIRST_STAGE..=EXTRA_STAGE).contains(&stage) {
+                    return Err(format!("Stage {} does not exist", stage));
+                }
+                // Main loop loads the script when stage changes
+                context.run.stage = stage;
+                context.run.boss = None;
+                context.run.is_cheated = true;
+                *context.game_state = GameState::Playing;
+                Ok(format!("Stage {}", stage))
+            },
+        },
+        ConsoleCommand {
+            name: "spell",
+            usage: "spell <name>",
+            run: |context, args| {
+                if args.is_empty() {
+                    return Err("Spell card name is missing".to_string());
+                }
+                let name: String = args.join(" ");
+                context.run.boss = Some(BossStatus {
+                    health: 1f32,
+                    spell_name: Some(name.clone()),
+                    spell_timer: CONSOLE_SPELL_TIME,
+                });
+                context.run.is_cheated = true;
+                *context.game_state = GameState::Playing;
+                Ok(format!("Spell card {}", name))
+            },
+        },
+    ]
+}
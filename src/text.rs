@@ -0,0 +1,394 @@
+use raylib::prelude::*;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Clone, Copy)]
+pub enum TextEffect {
+    Plain,
+    Shadow { offset: f32, color: Color },
+    Outline { thickness: f32, color: Color },
+}
+
+/// How text is drawn. Position passed to draw_text() is the left edge, center
+/// or right edge of every line depending on align
+#[derive(Clone, Copy)]
+pub struct TextStyle {
+    pub size: f32,
+    pub spacing: f32,
+    pub color: Color,
+    pub align: Align,
+    pub effect: TextEffect,
+    pub line_height: f32,
+    pub wrap_width: Option<f32>,
+}
+
+impl TextStyle {
+    pub const fn new(size: f32, color: Color) -> Self {
+        Self {
+            size,
+            spacing: 1f32,
+            color,
+            align: Align::Left,
+            effect: TextEffect::Plain,
+            line_height: size,
+            wrap_width: None,
+        }
+    }
+
+    pub const fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub const fn shadow(mut self, offset: f32, color: Color) -> Self {
+        self.effect = TextEffect::Shadow { offset, color };
+        self
+    }
+
+    pub const fn outline(mut self, thickness: f32, color: Color) -> Self {
+        self.effect = TextEffect::Outline { thickness, color };
+        self
+    }
+
+    /// Breaks lines on word boundaries so none is wider than width
+    pub const fn wrap(mut self, width: f32) -> Self {
+        self.wrap_width = Some(width);
+        self
+    }
+
+    pub const fn line_height(mut self, line_height: f32) -> Self {
+        self.line_height = line_height;
+        self
+    }
+}
+
+/// Draws text with markup, alignment, wrapping and effect of the style.
+/// Alpha of the style color fades the whole text, markup colors included
+///
+/// Markup: "{#rrggbb}" or "{#rrggbbaa}" switches color, "{/}" goes back to
+/// style color, "{{" is a literal "{"
+pub fn draw_text(
+    d: &mut impl RaylibDraw,
+    font: &Font,
+    text: &str,
+    position: Vector2,
+    style: &TextStyle,
+) {
+    let style_color: Color = Color::new(style.color.r, style.color.g, style.color.b, 255u8);
+    let mut color: Color = style_color;
+    for (i, line) in layout_lines(font, text, style).iter().enumerate() {
+        let spans: Vec<(String, Color)> = parse_markup(line, style_color, color);
+        if let Some((_, last_color)) = spans.last() {
+            color = *last_color;
+        }
+
+        let width: f32 = spans_width(font, &spans, style);
+        let mut x: f32 = match style.align {
+            Align::Left => position.x,
+            Align::Center => position.x - width / 2f32,
+            Align::Right => position.x - width,
+        };
+        let y: f32 = position.y + style.line_height * i as f32;
+        for (span, span_color) in &spans {
+            draw_span(d, font, span, Vector2::new(x, y), *span_color, style);
+            x += font.measure_text(span, style.size, style.spacing).x + style.spacing;
+        }
+    }
+}
+
+/// Size of the whole text block as draw_text() would draw it
+pub fn text_size(font: &Font, text: &str, style: &TextStyle) -> Vector2 {
+    let lines: Vec<String> = layout_lines(font, text, style);
+    let width: f32 = lines
+        .iter()
+        .map(|line| {
+            let spans: Vec<(String, Color)> = parse_markup(line, style.color, style.color);
+            spans_width(font, &spans, style)
+        })
+        .fold(0f32, f32::max);
+    let height: f32 = match lines.len() {
+        0 => 0f32,
+        count => style.line_height * (count - 1) as f32 + style.size,
+    };
+    Vector2::new(width, height)
+}
+
+/// Splits text into lines on "\n" and wraps them if style has a width
+fn layout_lines(font: &Font, text: &str, style: &TextStyle) -> Vec<String> {
+    match style.wrap_width {
+        Some(width) => wrap_lines(text, width, |line| {
+            font.measure_text(&strip_markup(line), style.size, style.spacing)
+                .x
+        }),
+        None => text.lines().map(|line| line.to_string()).collect(),
+    }
+}
+
+/// Word wrap with a custom measure, a word longer than width gets a line of its own
+pub fn wrap_lines(text: &str, width: f32, measure: impl Fn(&str) -> f32) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for paragraph in text.lines() {
+        let mut line: String = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate: String = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            };
+            if line.is_empty() || measure(&candidate) <= width {
+                line = candidate;
+            } else {
+                lines.push(line);
+                line = word.to_string();
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Splits line into colored spans, color is where previous line stopped
+pub fn parse_markup(line: &str, style_color: Color, color: Color) -> Vec<(String, Color)> {
+    let mut spans: Vec<(String, Color)> = Vec::new();
+    let mut span: String = String::new();
+    let mut color: Color = color;
+    let mut rest: &str = line;
+
+    while let Some(start) = rest.find('{') {
+        span.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(after) = rest.strip_prefix("{{") {
+            span.push('{');
+            rest = after;
+            continue;
+        }
+        let tag: Option<(Color, usize)> = rest.find('}').and_then(|end| {
+            let tag_color: Color = match &rest[1..end] {
+                "/" => style_color,
+                tag => parse_hex_color(tag.strip_prefix('#')?)?,
+            };
+            Some((tag_color, end))
+        });
+        match tag {
+            Some((tag_color, end)) => {
+                if !span.is_empty() {
+                    spans.push((std::mem::take(&mut span), color));
+                }
+                color = tag_color;
+                rest = &rest[end + 1..];
+            }
+            None => {
+                // Not a tag, brace is drawn as is
+                span.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    span.push_str(rest);
+    if !span.is_empty() || spans.is_empty() {
+        spans.push((span, color));
+    }
+    spans
+}
+
+/// Text without markup tags
+pub fn strip_markup(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            parse_markup(line, Color::WHITE, Color::WHITE)
+                .into_iter()
+                .map(|(span, _)| span)
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn parse_hex_color(hex: &str) -> Option<Color> {
+    if !hex.is_ascii() || (hex.len() != 6 && hex.len() != 8) {
+        return None;
+    }
+    let channel = |index: usize| u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16).ok();
+    let alpha: u8 = if hex.len() == 8 { channel(3)? } else { 255u8 };
+    Some(Color::new(channel(0)?, channel(1)?, channel(2)?, alpha))
+}
+
+fn spans_width(font: &Font, spans: &[(String, Color)], style: &TextStyle) -> f32 {
+    let width: f32 = spans
+        .iter()
+        .map(|(span, _)| font.measure_text(span, style.size, style.spacing).x + style.spacing)
+        .sum();
+    (width - style.spacing).max(0f32)
+}
+
+fn draw_span(
+    d: &mut impl RaylibDraw,
+    font: &Font,
+    text: &str,
+    position: Vector2,
+    color: Color,
+    style: &TextStyle,
+) {
+    // Effects fade together with the text
+    let alpha: f32 = style.color.a as f32 / 255f32;
+    match style.effect {
+        TextEffect::Plain => {}
+        TextEffect::Shadow {
+            offset,
+            color: shadow_color,
+        } => d.draw_text_ex(
+            font,
+            text,
+            position + offset,
+            style.size,
+            style.spacing,
+            shadow_color.alpha(alpha * shadow_color.a as f32 / 255f32),
+        ),
+        TextEffect::Outline {
+            thickness,
+            color: outline_color,
+        } => {
+            const DIRECTIONS: [(f32, f32); 8] = [
+                (-1f32, -1f32),
+                (0f32, -1f32),
+                (1f32, -1f32),
+                (-1f32, 0f32),
+                (1f32, 0f32),
+                (-1f32, 1f32),
+                (0f32, 1f32),
+                (1f32, 1f32),
+            ];
+            for (x, y) in DIRECTIONS {
+                d.draw_text_ex(
+                    font,
+                    text,
+                    position + Vector2::new(x, y) * thickness,
+                    style.size,
+                    style.spacing,
+                    outline_color.alpha(alpha * outline_color.a as f32 / 255f32),
+                );
+            }
+        }
+    }
+    d.draw_text_ex(
+        font,
+        text,
+        position,
+        style.size,
+        style.spacing,
+        color.alpha(alpha * color.a as f32 / 255f32),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Every character is one unit wide
+    fn measure(text: &str) -> f32 {
+        text.chars().count() as f32
+    }
+
+    // Color equality goes through raylib, channels don't
+    fn spans(line: &str, style_color: Color, color: Color) -> Vec<(String, [u8; 4])> {
+        parse_markup(line, style_color, color)
+            .into_iter()
+            .map(|(span, color)| (span, [color.r, color.g, color.b, color.a]))
+            .collect()
+    }
+
+    const WHITE: [u8; 4] = [255u8, 255u8, 255u8, 255u8];
+    const RED: [u8; 4] = [255u8, 0u8, 0u8, 255u8];
+
+    #[test]
+    fn words_wrap_at_width() {
+        assert_eq!(
+            wrap_lines("one two three four", 9f32, measure),
+            vec!["one two", "three", "four"]
+        );
+        assert_eq!(
+            wrap_lines("first line\nsecond", 20f32, measure),
+            vec!["first line", "second"]
+        );
+    }
+
+    #[test]
+    fn long_word_gets_own_line() {
+        assert_eq!(
+            wrap_lines("a unbreakable b", 4f32, measure),
+            vec!["a", "unbreakable", "b"]
+        );
+        assert_eq!(
+            wrap_lines("unbreakable", 4f32, measure),
+            vec!["unbreakable"]
+        );
+    }
+
+    #[test]
+    fn empty_lines_are_kept() {
+        assert_eq!(wrap_lines("a\n\nb", 4f32, measure), vec!["a", "", "b"]);
+    }
+
+    #[test]
+    fn color_tags_split_spans() {
+        assert_eq!(
+            spans("a {#ff0000}b{/} c", Color::WHITE, Color::WHITE),
+            vec![
+                ("a ".to_string(), WHITE),
+                ("b".to_string(), [255u8, 0u8, 0u8, 255u8]),
+                (" c".to_string(), WHITE),
+            ]
+        );
+        assert_eq!(
+            spans("{#00ff0080}x", Color::WHITE, Color::WHITE),
+            vec![("x".to_string(), [0u8, 255u8, 0u8, 128u8])]
+        );
+    }
+
+    #[test]
+    fn color_carries_over_from_previous_line() {
+        assert_eq!(
+            spans("b{/}c", Color::WHITE, Color::RED),
+            vec![("b".to_string(), RED), ("c".to_string(), WHITE)]
+        );
+    }
+
+    #[test]
+    fn double_brace_is_literal() {
+        assert_eq!(
+            spans("{{#ff0000}", Color::WHITE, Color::WHITE),
+            vec![("{#ff0000}".to_string(), WHITE)]
+        );
+    }
+
+    #[test]
+    fn broken_tags_are_drawn_as_is() {
+        assert_eq!(
+            spans("a {#ff0000", Color::WHITE, Color::WHITE),
+            vec![("a {#ff0000".to_string(), WHITE)]
+        );
+        assert_eq!(
+            spans("{oops} {#12}", Color::WHITE, Color::WHITE),
+            vec![("{oops} {#12}".to_string(), WHITE)]
+        );
+    }
+
+    #[test]
+    fn empty_line_has_one_span() {
+        assert_eq!(
+            spans("", Color::WHITE, Color::RED),
+            vec![(String::new(), RED)]
+        );
+    }
+
+    #[test]
+    fn markup_is_stripped() {
+        assert_eq!(strip_markup("a{#ff0000}b{/}\n{{c"), "ab\n{c");
+    }
+}
@@ -0,0 +1,102 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+const APP_DIR_NAME: &str = "nmf";
+// Next to the executable it keeps every user file in the game directory
+const PORTABLE_MARKER_NAME: &str = "portable.txt";
+
+/// Where options and saves are written, resolved once per launch
+struct UserDirs {
+    config: PathBuf,
+    data: PathBuf,
+}
+
+static USER_DIRS: OnceLock<UserDirs> = OnceLock::new();
+
+/// Path of a file in the user config directory, "options.dat" -> "~/.config/nmf/options.dat"
+pub fn config_path(name: &str) -> String {
+    user_dirs().config.join(name).to_string_lossy().into_owned()
+}
+
+/// Path of a file or directory in the user data directory, saves, scores, replays, screenshots
+pub fn data_path(name: &str) -> String {
+    user_dirs().data.join(name).to_string_lossy().into_owned()
+}
+
+/// Copies a file that older versions wrote into the working directory. The old file is
+/// left where it is, the game directory may be read-only or another install's.
+/// Nothing happens if it is missing or the new file already exists
+pub fn migrate_legacy_file(legacy_path: &str, path: &str) {
+    if !Path::new(legacy_path).is_file() || Path::new(path).exists() {
+        return;
+    }
+    match fs::copy(legacy_path, path) {
+        Ok(_) => log_info!("Copied {} to {}", legacy_path, path),
+        Err(err) => log_error!("Can't copy {} to {}: {}", legacy_path, path, err),
+    }
+}
+
+fn user_dirs() -> &'static UserDirs {
+    USER_DIRS.get_or_init(|| {
+        let (config, data) = match portable_dir() {
+            Some(dir) => (dir.clone(), dir),
+            None => platform_dirs().unwrap_or_else(|| {
+                log_warn!("No user directory found, using the working directory");
+                (PathBuf::from("."), PathBuf::from("."))
+            }),
+        };
+        for dir in [&config, &data] {
+            if let Err(err) = fs::create_dir_all(dir) {
+                log_error!("{}: {}", dir.display(), err);
+            }
+        }
+        log_info!("Config in {}, data in {}", config.display(), data.display());
+        UserDirs { config, data }
+    })
+}
+
+/// Directory of the executable if the portable marker is there
+fn portable_dir() -> Option<PathBuf> {
+    let exe_dir: PathBuf = env::current_exe().ok()?.parent()?.to_path_buf();
+    if exe_dir.join(PORTABLE_MARKER_NAME).is_file() {
+        Some(exe_dir)
+    } else {
+        None
+    }
+}
+
+/// Config and data directories of the platform
+#[cfg(target_os = "windows")]
+fn platform_dirs() -> Option<(PathBuf, PathBuf)> {
+    let dir: PathBuf = absolute_env_path("APPDATA")?.join(APP_DIR_NAME);
+    Some((dir.clone(), dir))
+}
+
+/// Config and data directories of the platform
+#[cfg(target_os = "macos")]
+fn platform_dirs() -> Option<(PathBuf, PathBuf)> {
+    let dir: PathBuf = absolute_env_path("HOME")?
+        .join("Library/Application Support")
+        .join(APP_DIR_NAME);
+    Some((dir.clone(), dir))
+}
+
+/// Config and data directories of the platform, XDG base directories
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn platform_dirs() -> Option<(PathBuf, PathBuf)> {
+    let home: Option<PathBuf> = absolute_env_path("HOME");
+    let config: PathBuf = absolute_env_path("XDG_CONFIG_HOME")
+        .or_else(|| home.as_ref().map(|home| home.join(".config")))?;
+    let data: PathBuf = absolute_env_path("XDG_DATA_HOME")
+        .or_else(|| home.as_ref().map(|home| home.join(".local/share")))?;
+    Some((config.join(APP_DIR_NAME), data.join(APP_DIR_NAME)))
+}
+
+/// Relative and empty values are ignored, as XDG spec says
+fn absolute_env_path(name: &str) -> Option<PathBuf> {
+    env::var_os(name)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+}
@@ -0,0 +1,88 @@
+//! Plays every replay of tests/replays through nmf_sim and compares its state hashes
+//! with the checkpoints stored next to it, "<name>.rpy" goes with "<name>.hashes".
+//!
+//! After an intended gameplay change checkpoints are recorded again from the game directory:
+//! nmf_sim --replay tests/replays/<name>.rpy --record tests/replays/<name>.hashes
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+const REPLAY_DIR_PATH: &str = "tests/replays";
+
+#[test]
+fn replays_match_checkpoints() {
+    let mut replay_paths: Vec<PathBuf> = fs::read_dir(REPLAY_DIR_PATH)
+        .unwrap_or_else(|err| panic!("{}: {}", REPLAY_DIR_PATH, err))
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "rpy"))
+        .collect();
+    replay_paths.sort();
+    assert!(
+        !replay_paths.is_empty(),
+        "{} has no replays",
+        REPLAY_DIR_PATH
+    );
+
+    let mut failures: Vec<String> = Vec::new();
+    for replay_path in &replay_paths {
+        let hashes_path: PathBuf = replay_path.with_extension("hashes");
+        if !hashes_path.is_file() {
+            failures.push(format!("{} is missing", hashes_path.display()));
+            continue;
+        }
+        let output: Output = Command::new(env!("CARGO_BIN_EXE_nmf_sim"))
+            .arg("--replay")
+            .arg(replay_path)
+            .arg("--expect")
+            .arg(&hashes_path)
+            .output()
+            .expect("nmf_sim did not start");
+        if !output.status.success() {
+            failures.push(format!(
+                "{}\n{}",
+                replay_path.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n\n"));
+}
+
+#[test]
+fn divergence_names_exact_frame() {
+    const DIVERGED_FRAME: usize = 137usize;
+    let replay_path: PathBuf = PathBuf::from(REPLAY_DIR_PATH).join("stage1_normal_dodge.rpy");
+    let hashes: String = fs::read_to_string(replay_path.with_extension("hashes"))
+        .expect("checkpoints of the replay are missing");
+    // Hash of one frame is changed, as if gameplay went another way there,
+    // line of every frame starts with its number
+    let changed: String = hashes
+        .lines()
+        .map(|line| match line.split_once(' ') {
+            Some((frame, _)) if frame == DIVERGED_FRAME.to_string() => {
+                format!("{} {:016x}\n", frame, 0u64)
+            }
+            _ => format!("{}\n", line),
+        })
+        .collect();
+    let hashes_path: PathBuf = std::env::temp_dir().join("nmf_divergence_test.hashes");
+    fs::write(&hashes_path, changed).expect("temporary checkpoints can't be written");
+
+    let output: Output = Command::new(env!("CARGO_BIN_EXE_nmf_sim"))
+        .arg("--replay")
+        .arg(&replay_path)
+        .arg("--expect")
+        .arg(&hashes_path)
+        .output()
+        .expect("nmf_sim did not start");
+    let _ = fs::remove_file(&hashes_path);
+    let stderr: String = String::from_utf8_lossy(&output.stderr).to_string();
+    assert_eq!(output.status.code(), Some(1), "{}", stderr);
+    assert!(
+        stderr.contains(&format!("diverged at frame {},", DIVERGED_FRAME))
+            && stderr.contains(&format!("frame {} still matched", DIVERGED_FRAME - 1usize)),
+        "{}",
+        stderr
+    );
+}